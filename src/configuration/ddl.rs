@@ -0,0 +1,463 @@
+use crate::configuration::{FakerConfiguration, ConfigurationItem, variable_type_from_str};
+use crate::configuration::definitions::{VariableType, DateFormat, SqlDialect};
+use crate::configuration::schema::Schema;
+use crate::engine::writer::quote_identifier;
+use crate::rules::split_weight;
+use crate::error::FakerError;
+
+impl FakerConfiguration {
+    /// 按各列的名字和类型生成 CREATE TABLE 语句，不可为 null 的列加上 NOT NULL
+    pub fn create_table(&self, table: &str, dialect: SqlDialect) -> String {
+        let columns: Vec<String> = self.items.iter()
+            .map(|n| {
+                let nullable = n.nullable || n.var_type == VariableType::Null;
+                let not_null = if nullable { "" } else { " NOT NULL" };
+                format!("  {} {}{}", quote_identifier(dialect, &n.var_name), column_type(n, dialect), not_null)
+            })
+            .collect();
+        format!("CREATE TABLE {} (\n{}\n);\n", quote_identifier(dialect, table), columns.join(",\n"))
+    }
+}
+
+impl Schema {
+    /// 按生成的顺序输出每张表的 CREATE TABLE 语句，父表在子表之前
+    pub fn create_tables(&self, dialect: SqlDialect) -> String {
+        self.tables_in_order()
+            .map(|t| t.config.create_table(&t.name, dialect))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 列在各方言中的类型，输出为 Unix 时间戳的时间列使用整数类型
+fn column_type(item: &ConfigurationItem, dialect: SqlDialect) -> String {
+    use SqlDialect::*;
+    let unix = matches!(item.date_format, DateFormat::UnixSeconds | DateFormat::UnixMillis);
+    let name = match (&item.var_type, dialect) {
+        (VariableType::Date, _) | (VariableType::Timestamp, _) if unix => {
+            if dialect == Sqlite { "INTEGER" } else { "BIGINT" }
+        }
+        (VariableType::Integer, Sqlite) => "INTEGER",
+        (VariableType::Integer, _) => "BIGINT",
+        (VariableType::Float, MySql) => "DOUBLE",
+        (VariableType::Float, PostgreSql) => "DOUBLE PRECISION",
+        (VariableType::Float, Sqlite) => "REAL",
+        (VariableType::Decimal { precision, scale }, MySql) => return format!("DECIMAL({},{})", precision, scale),
+        (VariableType::Decimal { precision, scale }, _) => return format!("NUMERIC({},{})", precision, scale),
+        (VariableType::Boolean, Sqlite) => "INTEGER",
+        (VariableType::Boolean, _) => "BOOLEAN",
+        (VariableType::Date, MySql) | (VariableType::Timestamp, MySql) => "DATETIME",
+        (VariableType::Date, PostgreSql) => "TIMESTAMP",
+        (VariableType::Timestamp, PostgreSql) => "TIMESTAMP WITH TIME ZONE",
+        (VariableType::Time, MySql) | (VariableType::Time, PostgreSql) => "TIME",
+        (VariableType::Bytes, PostgreSql) => "BYTEA",
+        (VariableType::Bytes, _) => "BLOB",
+        (VariableType::Uuid, MySql) => "CHAR(36)",
+        (VariableType::Uuid, PostgreSql) => "UUID",
+        // SQLite 没有时间和 UUID 类型，与 null 列一样存为文本
+        _ => "TEXT",
+    };
+    name.to_string()
+}
+
+/// 导入的时间列默认生成的时间范围
+const IMPORT_DATE_RANGE: (&str, &str) = ("2020-01-01", "2024-12-31");
+
+/// 导入的字符串列默认生成的最大长度
+const IMPORT_STRING_LENGTH: u32 = 10;
+
+/// 读取一条或多条 CREATE TABLE 语句，生成初始的配置文件：
+/// 只有一张表时为 变量名||变量类型||数据制造规则 的配置文件，多张表时为带 [表名] 的 schema 文件。
+/// 规则按列名和类型选取：id、主键或自增列用 increase，*_at 和时间列用 date，email 用 regex，
+/// 引用了同一批中其他表的外键用 fk，enum('a','b') 用 enum，其他列按类型生成随机值；
+/// 没有 NOT NULL 的列类型后加 ?
+pub fn starter_config(ddl: &str) -> Result<String, FakerError> {
+    let tables = parse_create_tables(ddl)?;
+    if tables.is_empty() {
+        return Err(FakerError::DdlSyntax("no CREATE TABLE statement found".to_string()));
+    }
+    let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+    let mut out = String::new();
+    for t in &tables {
+        if tables.len() > 1 {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{}]\n", t.name));
+        }
+        for c in &t.columns {
+            let (var_type, rule) = default_rule(c, &t.name, &names);
+            let nullable = if c.not_null { "" } else { "?" };
+            out.push_str(&format!("{}||{}{}||{}\n", c.name, var_type, nullable, rule));
+        }
+    }
+    Ok(out)
+}
+
+/// CREATE TABLE 中的一张表
+#[derive(Debug)]
+struct DdlTable {
+    name: String,
+    columns: Vec<DdlColumn>,
+}
+
+#[derive(Debug, Default)]
+struct DdlColumn {
+    name: String,
+    /// 小写的类型，例如 varchar(255)、int unsigned
+    type_name: String,
+    /// enum('a','b') 的值
+    values: Vec<String>,
+    not_null: bool,
+    primary_key: bool,
+    auto_increment: bool,
+    /// 外键引用的表和列
+    references: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// 关键字、没有引号的标识符和数字
+    Word(String),
+    /// 引号中的标识符
+    Ident(String),
+    /// 单引号中的字符串
+    Str(String),
+    Punct(char),
+}
+
+impl Token {
+    fn is_word(&self, w: &str) -> bool {
+        matches!(self, Token::Word(s) if s.eq_ignore_ascii_case(w))
+    }
+
+    /// 作为标识符时的名字
+    fn name(&self) -> Option<&str> {
+        match self {
+            Token::Word(s) | Token::Ident(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// 切分 SQL，跳过空白和 -- # /* */ 注释
+fn tokenize(sql: &str) -> Result<Vec<Token>, FakerError> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    let unterminated = |what: &str| FakerError::DdlSyntax(format!("unterminated {}", what));
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if (c == '-' && chars.get(i + 1) == Some(&'-')) || c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let end = (i + 2..chars.len().saturating_sub(1)).find(|j| chars[*j] == '*' && chars[*j + 1] == '/')
+                .ok_or_else(|| unterminated("comment"))?;
+            i = end + 2;
+        } else if c == '\'' || c == '"' || c == '`' || (c == '[' && chars.get(i + 1) != Some(&']')) {
+            let close = if c == '[' { ']' } else { c };
+            let mut s = String::new();
+            i += 1;
+            loop {
+                let d = *chars.get(i).ok_or_else(|| unterminated(if c == '\'' { "string" } else { "identifier" }))?;
+                i += 1;
+                if d == close {
+                    // 引号写两次表示引号本身
+                    if chars.get(i) == Some(&close) && c != '[' {
+                        s.push(d);
+                        i += 1;
+                        continue;
+                    }
+                    break;
+                }
+                if d == '\\' && c == '\'' && i < chars.len() {
+                    s.push(chars[i]);
+                    i += 1;
+                    continue;
+                }
+                s.push(d);
+            }
+            tokens.push(if c == '\'' { Token::Str(s) } else { Token::Ident(s) });
+        } else if c.is_alphanumeric() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(Token::Punct(c));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// 列定义中类型之后的约束从这些关键字开始
+const COLUMN_CONSTRAINTS: &[&str] = &["not", "null", "default", "primary", "unique", "references", "auto_increment",
+    "autoincrement", "check", "collate", "comment", "generated", "constraint", "on", "as", "identity", "charset"];
+
+/// 表级的约束从这些关键字开始
+const TABLE_CONSTRAINTS: &[&str] = &["primary", "key", "index", "unique", "constraint", "foreign", "check", "fulltext",
+    "spatial", "exclude", "like"];
+
+fn parse_create_tables(sql: &str) -> Result<Vec<DdlTable>, FakerError> {
+    let tokens = tokenize(sql)?;
+    let mut tables = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        if !tokens[i].is_word("create") {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        while tokens.get(i).is_some_and(|t| ["temporary", "temp", "unlogged", "or", "replace"].iter().any(|w| t.is_word(w))) {
+            i += 1;
+        }
+        // 例如 CREATE INDEX，跳到下一条语句
+        if !tokens.get(i).is_some_and(|t| t.is_word("table")) {
+            continue;
+        }
+        i += 1;
+        if tokens.get(i).is_some_and(|t| t.is_word("if")) {
+            i += 3;
+        }
+        let name = qualified_name(&tokens, &mut i)?;
+        if tokens.get(i) != Some(&Token::Punct('(')) {
+            return Err(FakerError::DdlSyntax(format!("expected `(` after table `{}`", name)));
+        }
+        let elements = split_elements(&tokens, &mut i)?;
+        let table = parse_table(name, &elements)?;
+        if tables.iter().any(|t: &DdlTable| t.name == table.name) {
+            return Err(FakerError::DuplicateTable(table.name));
+        }
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+/// schema.table 这样的名字只保留最后一段
+fn qualified_name(tokens: &[Token], i: &mut usize) -> Result<String, FakerError> {
+    loop {
+        let name = tokens.get(*i).and_then(|t| t.name())
+            .ok_or_else(|| FakerError::DdlSyntax("expected a table name".to_string()))?;
+        *i += 1;
+        if tokens.get(*i) != Some(&Token::Punct('.')) {
+            return Ok(name.to_string());
+        }
+        *i += 1;
+    }
+}
+
+/// 从 ( 开始，按最外层的逗号切分到对应的 ) 为止
+fn split_elements(tokens: &[Token], i: &mut usize) -> Result<Vec<Vec<Token>>, FakerError> {
+    let mut elements = vec![vec![]];
+    let mut depth = 0;
+    *i += 1;
+    loop {
+        let t = tokens.get(*i).ok_or_else(|| FakerError::DdlSyntax("missing `)`".to_string()))?;
+        *i += 1;
+        match t {
+            Token::Punct('(') => depth += 1,
+            Token::Punct(')') if depth == 0 => break,
+            Token::Punct(')') => depth -= 1,
+            Token::Punct(',') if depth == 0 => {
+                elements.push(vec![]);
+                continue;
+            }
+            _ => {}
+        }
+        elements.last_mut().unwrap().push(t.clone());
+    }
+    Ok(elements.into_iter().filter(|e| !e.is_empty()).collect())
+}
+
+fn parse_table(name: String, elements: &[Vec<Token>]) -> Result<DdlTable, FakerError> {
+    let mut table = DdlTable { name, columns: vec![] };
+    // 表级约束写在列之后，先解析所有的列
+    for e in elements.iter().filter(|e| !TABLE_CONSTRAINTS.iter().any(|w| e[0].is_word(w))) {
+        table.columns.push(parse_column(e)?);
+    }
+    for e in elements.iter().filter(|e| TABLE_CONSTRAINTS.iter().any(|w| e[0].is_word(w))) {
+        let position = |w: &str| e.iter().position(|t| t.is_word(w));
+        if let (Some(_), Some(k)) = (position("primary"), position("key")) {
+            for c in parenthesized(&e[k + 1..]) {
+                if let Some(col) = table.columns.iter_mut().find(|col| col.name == c) {
+                    col.primary_key = true;
+                    col.not_null = true;
+                }
+            }
+        }
+        if let (Some(f), Some(r)) = (position("foreign"), position("references")) {
+            let columns = parenthesized(&e[f..r]);
+            let mut j = r + 1;
+            let parent = qualified_name(e, &mut j)?;
+            for (c, key) in columns.iter().zip(parenthesized(&e[j..])) {
+                if let Some(col) = table.columns.iter_mut().find(|col| col.name == *c) {
+                    col.references = Some((parent.clone(), key));
+                }
+            }
+        }
+    }
+    Ok(table)
+}
+
+/// 第一对括号中的名字，例如 (a, b)
+fn parenthesized(tokens: &[Token]) -> Vec<String> {
+    tokens.iter()
+        .skip_while(|t| **t != Token::Punct('('))
+        .take_while(|t| **t != Token::Punct(')'))
+        .filter_map(|t| t.name().map(|n| n.to_string()))
+        .collect()
+}
+
+fn parse_column(tokens: &[Token]) -> Result<DdlColumn, FakerError> {
+    let mut column = DdlColumn {
+        name: tokens[0].name()
+            .ok_or_else(|| FakerError::DdlSyntax(format!("expected a column name, found {:?}", tokens[0])))?
+            .to_string(),
+        ..DdlColumn::default()
+    };
+    let mut i = 1;
+    let mut depth = 0;
+    while let Some(t) = tokens.get(i) {
+        let constraint = depth == 0 && !column.type_name.is_empty()
+            && (COLUMN_CONSTRAINTS.iter().any(|w| t.is_word(w))
+                || (t.is_word("character") && tokens.get(i + 1).is_some_and(|n| n.is_word("set"))));
+        if constraint {
+            break;
+        }
+        match t {
+            Token::Punct('(') => {
+                depth += 1;
+                column.type_name.push('(');
+            }
+            Token::Punct(')') => {
+                depth -= 1;
+                column.type_name.push(')');
+            }
+            Token::Punct(c) => column.type_name.push(*c),
+            // MySQL 的 enum("a") 中双引号也是字符串
+            Token::Str(s) | Token::Ident(s) if depth > 0 => {
+                column.values.push(s.clone());
+                column.type_name.push_str("''");
+            }
+            Token::Str(w) | Token::Word(w) | Token::Ident(w) => {
+                if column.type_name.ends_with(|c: char| c.is_alphanumeric() || c == ')') {
+                    column.type_name.push(' ');
+                }
+                column.type_name.push_str(&w.to_lowercase());
+            }
+        }
+        i += 1;
+    }
+    if column.type_name.is_empty() {
+        return Err(FakerError::DdlSyntax(format!("column `{}` has no type", column.name)));
+    }
+
+    while let Some(t) = tokens.get(i) {
+        if t.is_word("not") && tokens.get(i + 1).is_some_and(|n| n.is_word("null")) {
+            column.not_null = true;
+            i += 1;
+        } else if t.is_word("primary") {
+            column.primary_key = true;
+            column.not_null = true;
+        } else if t.is_word("auto_increment") || t.is_word("autoincrement") || t.is_word("identity") {
+            column.auto_increment = true;
+        } else if t.is_word("references") {
+            let mut j = i + 1;
+            let parent = qualified_name(tokens, &mut j)?;
+            if let Some(key) = parenthesized(&tokens[j..]).pop() {
+                column.references = Some((parent, key));
+            }
+        }
+        i += 1;
+    }
+    if column.type_name.contains("serial") {
+        column.auto_increment = true;
+        column.not_null = true;
+    }
+    Ok(column)
+}
+
+/// 列在配置文件中的类型和规则，table 为列所在的表，tables 为同一批导入的表。
+/// 引用自己所在的表的外键（例如 manager_id REFERENCES emp(id)）无法用 fk 生成，按类型生成
+fn default_rule(c: &DdlColumn, table: &str, tables: &[&str]) -> (String, String) {
+    let (type_name, var_type) = config_type(&c.type_name);
+    let name = c.name.to_lowercase();
+    let rule = match &var_type {
+        _ if c.references.as_ref()
+            .is_some_and(|(t, _)| tables.contains(&t.as_str()) && !t.eq_ignore_ascii_case(table)) => {
+            let (t, key) = c.references.as_ref().unwrap();
+            format!("fk({}.{})", t, key)
+        }
+        VariableType::Integer | VariableType::String if name == "id" || c.auto_increment || c.primary_key => "increase(1,1)".to_string(),
+        VariableType::String if !c.values.is_empty() => {
+            let values: Vec<String> = c.values.iter().map(|v| quote_argument(v)).collect();
+            format!("enum({})", values.join(","))
+        }
+        VariableType::Date | VariableType::Timestamp | VariableType::Time => date_rule(),
+        VariableType::String if name.ends_with("_at") => date_rule(),
+        VariableType::String if name.contains("email") => {
+            r#"regex("[a-z]{3,8}\.[a-z]{3,8}@example\.(com|org|net)")"#.to_string()
+        }
+        VariableType::Integer => "randint(0,100)".to_string(),
+        VariableType::Float => "randfloat(0,100)".to_string(),
+        VariableType::Decimal { precision, scale } => {
+            // 整数部分放不下 100 时取能放下的最大值
+            let max = if precision > scale {
+                (10f64.powi((precision - scale) as i32) - 1.0).min(100.0)
+            } else {
+                0.5
+            };
+            format!("randfloat(0,{})", max)
+        }
+        VariableType::Boolean => "enum(true,false)".to_string(),
+        VariableType::Uuid => "uuid()".to_string(),
+        VariableType::Bytes => "enum(data)".to_string(),
+        _ => {
+            let max = varchar_length(&c.type_name).unwrap_or(IMPORT_STRING_LENGTH).clamp(1, IMPORT_STRING_LENGTH);
+            format!("regex(\"[a-z]{{{},{}}}\")", max.min(3), max)
+        }
+    };
+    (type_name, rule)
+}
+
+fn date_rule() -> String {
+    format!("date({},{})", IMPORT_DATE_RANGE.0, IMPORT_DATE_RANGE.1)
+}
+
+/// 能解析的类型原样保留，否则依次去掉括号中的参数和类型后面的修饰，都不行时作为 varchar
+fn config_type(type_name: &str) -> (String, VariableType) {
+    let stripped = match (type_name.find('('), type_name.rfind(')')) {
+        (Some(l), Some(r)) if l < r => format!("{}{}", &type_name[..l], &type_name[r + 1..]),
+        _ => type_name.to_string(),
+    };
+    // 例如 text[] 中的 text
+    let first: String = stripped.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    for t in [type_name.to_string(), stripped, first] {
+        if let Ok(v) = variable_type_from_str(&t) {
+            return (t, v);
+        }
+    }
+    ("varchar".to_string(), VariableType::String)
+}
+
+/// varchar(32) 中的 32
+fn varchar_length(type_name: &str) -> Option<u32> {
+    let l = type_name.find('(')?;
+    let r = type_name[l..].find(')')? + l;
+    type_name[l + 1..r].trim().parse().ok()
+}
+
+/// 规则的参数中需要引号时加上双引号，以 :数字 结尾的值也要加上，以免被 enum 当成权重
+fn quote_argument(s: &str) -> String {
+    if s.is_empty() || s.contains(|c: char| ",()\"'\\".contains(c)) || s.trim() != s || split_weight(s).is_some() {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s.to_string()
+    }
+}
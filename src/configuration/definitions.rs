@@ -1,36 +1,136 @@
-use std::io::ErrorKind;
 use std::fmt::Debug;
-use std::borrow::Borrow;
-use std::num::ParseIntError;
-use crate::rules::{IncreaseRule, EnumRule, EnumFileRule};
-use std::sync::{Arc, Mutex};
-use chrono::{Utc, DateTime};
+use crate::rules::{IncreaseRule, EnumRule, EnumFileRule, RandIntRule, RandFloatRule, DateRule, DateIncreaseRule, NowRule, UuidRule, RegexRule, FormatRule,
+                   ConcatRule, RefRule, DateAfterRule, FkRule};
+use crate::error::FakerError;
+use crate::rules::parse_datetime;
+use crate::configuration::expression::parse_rule;
+use chrono::{Utc, DateTime, FixedOffset, NaiveDate, NaiveTime};
+use rand::rngs::StdRng;
+
+/// Date 类型默认的输出格式
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Time 类型默认的输出格式
+pub const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+
+/// Date、Timestamp 和 Time 列的输出格式
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateFormat {
+    /// strftime 格式，例如 %Y-%m-%d %H:%M:%S
+    Pattern(String),
+    /// RFC 3339，例如 2020-01-01T08:00:00+08:00
+    Rfc3339,
+    /// Unix 时间戳（秒），输出为整数
+    UnixSeconds,
+    /// Unix 时间戳（毫秒），输出为整数
+    UnixMillis,
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat::Pattern(DEFAULT_DATE_FORMAT.to_string())
+    }
+}
+
+impl DateFormat {
+    /// 各类型默认的输出格式，Timestamp 带时区所以默认用 RFC 3339
+    pub fn default_for(var_type: &VariableType) -> Self {
+        match var_type {
+            VariableType::Timestamp => DateFormat::Rfc3339,
+            VariableType::Time => DateFormat::Pattern(DEFAULT_TIME_FORMAT.to_string()),
+            _ => DateFormat::default(),
+        }
+    }
+
+    /// 按格式和时区输出时间，Unix 时间戳输出为 JSON 数字，其他输出为字符串
+    pub fn format(&self, d: &DateTime<Utc>, time_zone: &FixedOffset) -> serde_json::Value {
+        let local = d.with_timezone(time_zone);
+        match self {
+            DateFormat::Pattern(p) => serde_json::Value::String(local.format(p).to_string()),
+            DateFormat::Rfc3339 => serde_json::Value::String(local.to_rfc3339()),
+            DateFormat::UnixSeconds => serde_json::Value::from(d.timestamp()),
+            DateFormat::UnixMillis => serde_json::Value::from(d.timestamp_millis()),
+        }
+    }
+}
 
 /// 输出格式的枚举
 #[derive(Debug, Clone, PartialEq)]
 pub enum OutPutType {
     Json,
+    /// JSON Lines（NDJSON），每行一个 JSON 对象
+    JsonLines,
     Csv,
+    /// 批量的 INSERT 语句，每条语句最多插入 batch_size 行
+    Sql {
+        dialect: SqlDialect,
+        batch_size: usize,
+    },
+    Parquet,
+    /// Avro object container 文件，schema 由各列生成
+    Avro,
     //...
 }
 
+/// SQL 输出的方言，决定标识符、字符串、布尔值和二进制的写法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqlDialect {
+    MySql,
+    PostgreSql,
+    Sqlite,
+}
+
+impl OutPutType {
+    /// 输出文件的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutPutType::Json => "json",
+            OutPutType::JsonLines => "jsonl",
+            OutPutType::Csv => "csv",
+            OutPutType::Sql { .. } => "sql",
+            OutPutType::Parquet => "parquet",
+            OutPutType::Avro => "avro",
+        }
+    }
+}
+
 /// 包装输出格式的对象的枚举
 #[derive(Debug, Clone)]
 pub enum OutPutTypeValue {
     Json(serde_json::Value),
-    Csv(Vec<String>),
+    /// None 表示 null，由 CSV 输出的 null 选项决定写成什么
+    Csv(Vec<Option<String>>),
+    /// 各列的值，由 SQL、Parquet 等输出按列的类型写出
+    Values(Vec<ColumnValue>),
 }
 
-/// Integer -> 对应 int、integer、smallint和numeric 等等
-/// Float -> float、real和double, precision
-/// String -> char、varchar、binary、varbinary、blob、text、enum和set
-/// Date -> datetime、date、timestamp、time和year
+/// Integer -> 对应 int、integer、smallint、bigint 和 serial 等等
+/// Float -> float、real、double 和 double precision
+/// String -> char、varchar、text、enum、set 和 json
+/// Date -> datetime、date 和 year
+/// Boolean -> bool、boolean 和 bit
+/// Decimal -> decimal(p,s)、numeric(p,s)，precision 为总位数，scale 为小数位数
+/// Time -> time
+/// Timestamp -> timestamp、timestamptz，输出时带时区
+/// Bytes -> binary、varbinary、blob 和 bytea，输出为十六进制
+/// Uuid -> uuid
+/// Null -> null，总是输出 null
 #[derive(Debug, Clone, PartialEq)]
 pub enum VariableType {
     Integer,
     Float,
     String,
     Date,
+    Boolean,
+    Decimal {
+        precision: u32,
+        scale: u32,
+    },
+    Time,
+    Timestamp,
+    Bytes,
+    Uuid,
+    Null,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,16 +139,245 @@ pub enum VariableTypeValue {
     Float(f64),
     String(String),
     Date(DateTime<Utc>),
+    Boolean(bool),
+    /// 定点小数，真实值为 value / 10^scale
+    Decimal {
+        value: i128,
+        scale: u32,
+    },
+    Time(NaiveTime),
+    Timestamp(DateTime<Utc>),
+    Bytes(Vec<u8>),
+    Uuid(u128),
+    Null,
+}
+
+impl VariableTypeValue {
+    /// 值是否属于该类型
+    pub fn matches(&self, var_type: &VariableType) -> bool {
+        match (var_type, self) {
+            (_, VariableTypeValue::Null) => true,
+            (VariableType::Decimal { scale, .. }, VariableTypeValue::Decimal { scale: s, .. }) => scale == s,
+            (VariableType::Integer, VariableTypeValue::Integer(_))
+            | (VariableType::Float, VariableTypeValue::Float(_))
+            | (VariableType::String, VariableTypeValue::String(_))
+            | (VariableType::Date, VariableTypeValue::Date(_))
+            | (VariableType::Boolean, VariableTypeValue::Boolean(_))
+            | (VariableType::Time, VariableTypeValue::Time(_))
+            | (VariableType::Timestamp, VariableTypeValue::Timestamp(_))
+            | (VariableType::Bytes, VariableTypeValue::Bytes(_))
+            | (VariableType::Uuid, VariableTypeValue::Uuid(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// 把字符串（例如 enum 的值）转换成对应类型的值，失败时返回原因
+    pub fn parse(s: &str, var_type: &VariableType) -> Result<VariableTypeValue, String> {
+        match var_type {
+            VariableType::Integer => s.parse::<i64>().map(VariableTypeValue::Integer).map_err(|e| e.to_string()),
+            VariableType::Float => s.parse::<f64>().map(VariableTypeValue::Float).map_err(|e| e.to_string()),
+            VariableType::String => Ok(VariableTypeValue::String(s.to_string())),
+            VariableType::Date | VariableType::Timestamp | VariableType::Time => {
+                let d = parse_datetime(s).or_else(|| {
+                    // Time 列也接受只有时间的值
+                    NaiveTime::parse_from_str(s.trim(), DEFAULT_TIME_FORMAT).ok()
+                        .filter(|_| *var_type == VariableType::Time)
+                        .and_then(|t| NaiveDate::from_ymd_opt(1970, 1, 1).map(|d| d.and_time(t)))
+                        .map(|d| DateTime::from_naive_utc_and_offset(d, Utc))
+                });
+                match d {
+                    Some(d) => Ok(VariableTypeValue::from_datetime(d, var_type).unwrap()),
+                    None => Err("expected a date like 2020-01-01 00:00:00".to_string()),
+                }
+            }
+            VariableType::Boolean => match s.trim().to_lowercase().as_str() {
+                "true" | "t" | "1" | "yes" => Ok(VariableTypeValue::Boolean(true)),
+                "false" | "f" | "0" | "no" => Ok(VariableTypeValue::Boolean(false)),
+                _ => Err("expected true or false".to_string()),
+            },
+            VariableType::Decimal { precision, scale } => parse_decimal(s, *precision, *scale)
+                .map(|value| VariableTypeValue::Decimal { value, scale: *scale }),
+            VariableType::Bytes => Ok(VariableTypeValue::Bytes(s.as_bytes().to_vec())),
+            VariableType::Uuid => parse_uuid(s).map(VariableTypeValue::Uuid).ok_or_else(|| "expected a UUID".to_string()),
+            VariableType::Null => Ok(VariableTypeValue::Null),
+        }
+    }
+
+    /// 把时间转换成 Date、Timestamp、Time 或 String 类型的值
+    pub fn from_datetime(d: DateTime<Utc>, var_type: &VariableType) -> Option<VariableTypeValue> {
+        match var_type {
+            VariableType::Date => Some(VariableTypeValue::Date(d)),
+            VariableType::Timestamp => Some(VariableTypeValue::Timestamp(d)),
+            VariableType::Time => Some(VariableTypeValue::Time(d.time())),
+            VariableType::String => Some(VariableTypeValue::String(d.format(DEFAULT_DATE_FORMAT).to_string())),
+            _ => None,
+        }
+    }
+
+    /// 把浮点数转换成 Float、Decimal 或 String 类型的值，Decimal 超出精度时返回 None
+    pub fn from_f64(v: f64, var_type: &VariableType) -> Option<VariableTypeValue> {
+        match var_type {
+            VariableType::Float => Some(VariableTypeValue::Float(v)),
+            VariableType::String => Some(VariableTypeValue::String(v.to_string())),
+            VariableType::Decimal { precision, scale } => {
+                let value = (v * 10f64.powi(*scale as i32)).round();
+                if value.abs() >= 10f64.powi(*precision as i32) {
+                    return None;
+                }
+                Some(VariableTypeValue::Decimal { value: value as i128, scale: *scale })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decimal 支持的最大总位数
+pub const MAX_DECIMAL_PRECISION: u32 = 38;
+
+/// 把 12.345 这样的字符串按 scale 四舍五入成定点小数，总位数超过 precision 时返回错误
+pub fn parse_decimal(s: &str, precision: u32, scale: u32) -> Result<i128, String> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int_part, frac_part) = match digits.find('.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (digits, ""),
+    };
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+        return Err("expected a decimal number".to_string());
+    }
+    let overflow = || format!("does not fit in decimal({},{})", precision, scale);
+    let mut value: i128 = 0;
+    for c in int_part.chars().chain(frac_part.chars().chain(std::iter::repeat('0')).take(scale as usize)) {
+        value = value.checked_mul(10)
+            .and_then(|v| v.checked_add(c.to_digit(10).unwrap() as i128))
+            .ok_or_else(overflow)?;
+    }
+    // 按被舍去的第一位四舍五入
+    if frac_part.chars().nth(scale as usize).is_some_and(|c| c >= '5') {
+        value += 1;
+    }
+    if value >= 10i128.pow(precision) {
+        return Err(overflow());
+    }
+    Ok(if negative { -value } else { value })
+}
+
+/// 定点小数输出为字符串，例如 value = -1205, scale = 2 输出 -12.05
+pub fn decimal_to_string(value: i128, scale: u32) -> String {
+    if scale == 0 {
+        return value.to_string();
+    }
+    let scale_pow = 10i128.pow(scale);
+    let sign = if value < 0 { "-" } else { "" };
+    let abs = value.unsigned_abs();
+    format!("{}{}.{:0width$}", sign, abs / scale_pow as u128, abs % scale_pow as u128, width = scale as usize)
+}
+
+/// 把 16 字节的随机数转换成 RFC 4122 第 4 版的 UUID
+pub fn uuid_v4(bytes: u128) -> u128 {
+    (bytes & !(0xF000u128 << 64) & !(0xC000u128 << 48)) | (0x4000u128 << 64) | (0x8000u128 << 48)
+}
+
+/// UUID 输出为 8-4-4-4-12 的小写十六进制
+pub fn uuid_to_string(u: u128) -> String {
+    let h = format!("{:032x}", u);
+    format!("{}-{}-{}-{}-{}", &h[..8], &h[8..12], &h[12..16], &h[16..20], &h[20..])
+}
+
+fn parse_uuid(s: &str) -> Option<u128> {
+    let h: String = s.trim().chars().filter(|c| *c != '-').collect();
+    if h.len() != 32 {
+        return None;
+    }
+    u128::from_str_radix(&h, 16).ok()
 }
 
 pub trait ConstructRule {
-    fn construct(s: String) -> Result<Box<Self>, ()>;
+    fn construct(s: String) -> Result<Box<Self>, FakerError>;
+}
+
+/// 生成一个值时传给规则的上下文
+#[derive(Debug)]
+pub struct GenerateContext<'a> {
+    /// 当前行号，从 0 开始
+    pub row: u64,
+    /// 由种子、行号和列决定的随机数生成器，规则的所有随机性都应来自这里
+    pub rng: StdRng,
+    /// 同一行中已经生成的列
+    pub columns: RowValues<'a>,
+    /// 同一个 schema 中的其他表，只有一张表时为 None
+    pub tables: Option<&'a dyn Tables>,
+}
+
+/// schema 中的所有表，外键按行号重新生成父表中的值，因此不需要保存已经生成的数据
+pub trait Tables: Debug + Sync {
+    /// 表的行数，没有该表时返回 None
+    fn row_count(&self, table: &str) -> Option<u64>;
+
+    /// 表中第 row 行 column 列的值，与生成该表时得到的值相同
+    fn value(&self, table: &str, column: &str, row: u64) -> Result<ColumnValue, FakerError>;
+}
+
+/// 同一行中已经生成的一列
+#[derive(Debug, Clone)]
+pub struct ColumnValue {
+    pub value: VariableTypeValue,
+    /// 按列的输出格式转换成的 JSON
+    pub rendered: serde_json::Value,
+}
+
+impl ColumnValue {
+    /// 输出为文本时的值，null 返回 None
+    pub fn text(&self) -> Option<String> {
+        match &self.rendered {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            v => Some(v.to_string()),
+        }
+    }
+}
+
+/// 同一行中各列的值，还没有生成的列为 None
+#[derive(Debug, Clone, Copy)]
+pub struct RowValues<'a> {
+    pub names: &'a [&'a str],
+    pub values: &'a [Option<ColumnValue>],
+}
+
+impl<'a> RowValues<'a> {
+    /// 按列名查找已经生成的列
+    pub fn get(&self, name: &str) -> Option<&'a ColumnValue> {
+        let idx = self.names.iter().position(|n| *n == name)?;
+        self.values.get(idx)?.as_ref()
+    }
 }
 
 ///代表数据生成规则的trait
 pub trait GenerateRule: Debug + GenerateRuleClone + Sync + Send {
     ///生成对应数据类型的数据，如果不能生成则返回Err（基本Err是 address 无法作为 Integer 输出）
-    fn generate_into(&self, into_type: VariableType) -> Result<VariableTypeValue, ()>;
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError>;
+
+    ///读取配置时检查规则能否生成该类型的数据，避免到生成时才出错
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError>;
+
+    ///规则引用的同一行中其他列的列名，这些列会先生成
+    fn references(&self) -> Vec<String> {
+        vec![]
+    }
+
+    ///读取配置时检查引用的列的类型，types 与 references() 一一对应
+    fn check_references(&self, _types: &[VariableType], _into_type: &VariableType) -> Result<(), FakerError> {
+        Ok(())
+    }
+
+    ///规则引用的其他表中的列 (表名, 列名)，这些表会先生成
+    fn foreign_keys(&self) -> Vec<(String, String)> {
+        vec![]
+    }
 }
 
 pub trait GenerateRuleClone {
@@ -70,49 +399,37 @@ impl Clone for Box<dyn GenerateRule> {
 }
 
 macro_rules! match_rules {
-    ($s:expr, $rule_name:expr, $struct_name:ty) => {
-        if $s.starts_with($rule_name) {
-        return match <$struct_name>::construct($s) {
-            Ok(e) => {
-                Ok(e as Box<dyn GenerateRule>)
-            }
-            Err(_) => {
-                Err(())
-            }
-        };
-    }
+    ($call:expr, $rule_name:expr, $struct_name:ty) => {
+        if $call.name.eq_ignore_ascii_case($rule_name) {
+            return <$struct_name>::construct($call.source.clone()).map(|e| e as Box<dyn GenerateRule>);
+        }
     };
 }
 
-/////识别Configuration的第三列数据，构造规则，若输入不符合要求返回Err(())
-pub fn construct_from_str(config_str: &str) -> Result<Box<dyn GenerateRule>, ()> {
-    let s = config_str.trim().to_lowercase();
-    match_rules!(s,"increase(",IncreaseRule);
-    match_rules!(s,"enum(",EnumRule);
-    match_rules!(s,"enum_file(",EnumFileRule);
+///识别Configuration的第三列数据，构造规则
+pub fn construct_from_str(config_str: &str) -> Result<Box<dyn GenerateRule>, FakerError> {
+    // 只有规则名不区分大小写，参数（例如文件路径）保持原样
+    let call = parse_rule(config_str)?;
+    match_rules!(call,"increase",IncreaseRule);
+    match_rules!(call,"enum",EnumRule);
+    match_rules!(call,"enum_file",EnumFileRule);
+    match_rules!(call,"randint",RandIntRule);
+    match_rules!(call,"randfloat",RandFloatRule);
+    match_rules!(call,"date",DateRule);
+    match_rules!(call,"date_increase",DateIncreaseRule);
+    match_rules!(call,"now",NowRule);
+    match_rules!(call,"uuid",UuidRule);
+    match_rules!(call,"regex",RegexRule);
+    match_rules!(call,"format",FormatRule);
+    match_rules!(call,"concat",ConcatRule);
+    match_rules!(call,"ref",RefRule);
+    match_rules!(call,"date_after",DateAfterRule);
+    match_rules!(call,"fk",FkRule);
 
-    Err(())
+    Err(FakerError::UnknownRule(call.name))
 }
 
-/// 将 foo(var1,var2,var3) 简单变成 [var1,var2,var3] 的vec
-pub fn remove_parentheses(s: &String) -> Result<Vec<&str>, ()> {
-    let left_brackets_idx = match s.find("(") {
-        None => {
-            return Err(());
-        }
-        Some(e) => {
-            e
-        }
-    };
-    let right_brackets_idx = match s.find(")") {
-        None => {
-            return Err(());
-        }
-        Some(e) => {
-            e
-        }
-    };
-    let sub_str = &s[left_brackets_idx + 1..right_brackets_idx];
-    let args: Vec<&str> = sub_str.split(",").collect();
-    return Ok(args);
+/// 将 foo(var1, "var,2", var3) 变成 [var1,var,2,var3] 的vec，嵌套的规则保留原文
+pub fn remove_parentheses(s: &str) -> Result<Vec<String>, FakerError> {
+    Ok(parse_rule(s)?.args.iter().map(|a| a.text().to_string()).collect())
 }
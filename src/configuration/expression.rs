@@ -0,0 +1,220 @@
+use crate::error::FakerError;
+
+/// 一个规则表达式，例如 enum("Smith, John", Jones) 或 format("{}-{}", randint(1,9), name)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleCall {
+    /// 规则名，保持原样，比较时不区分大小写
+    pub name: String,
+    pub args: Vec<Argument>,
+    /// 该调用在配置中的原文，用于构造嵌套的规则
+    pub source: String,
+}
+
+/// 规则的一个参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    /// 普通的值，已经去掉首尾空白和引号并处理了转义；
+    /// quoted 表示值以引号开头，例如 "" 是一个空的字符串而不是没有写值
+    Value {
+        text: String,
+        quoted: bool,
+    },
+    /// 嵌套的规则
+    Call(RuleCall),
+}
+
+impl Argument {
+    /// 参数的文本，嵌套的规则返回原文
+    pub fn text(&self) -> &str {
+        match self {
+            Argument::Value { text, .. } => text,
+            Argument::Call(c) => &c.source,
+        }
+    }
+}
+
+/// 解析整个规则表达式：
+/// - 参数以 , 分隔，首尾的空白会被去掉，大小写保持原样
+/// - 含有 , ( ) 或首尾空白的值写在 "" 或 '' 中，引号中可以用 \" \' \\ \n \t \r 转义，
+///   引号外可以用 \" \' \\ \, \( \) 转义，其他的 \ 保持原样
+/// - 标识符后紧跟 ( 的参数是嵌套的规则，例如 format("{}", randint(1,9))
+/// - foo() 没有参数，foo(,) 有两个空参数
+pub fn parse_rule(s: &str) -> Result<RuleCall, FakerError> {
+    let mut p = Parser { s, pos: 0 };
+    p.skip_whitespace();
+    let call = p.call()?;
+    p.skip_whitespace();
+    if p.pos != s.len() {
+        return Err(p.error("unexpected text after `)`"));
+    }
+    Ok(call)
+}
+
+/// 按 || 把配置的一行分成各列，引号中和 \ 之后的 | 不算，例如 format("{}||{}",a,b)
+pub fn split_columns(line: &str) -> Vec<&str> {
+    let mut p = Parser { s: line, pos: 0 };
+    let mut columns = vec![];
+    let mut start = 0;
+    while let Some(c) = p.bump() {
+        match c {
+            // 没有结束的引号留给规则的解析报错
+            '"' | '\'' if p.quoted(c, &mut String::new()).is_err() => break,
+            '\\' => {
+                p.bump();
+            }
+            '|' if p.peek() == Some('|') => {
+                columns.push(&line[start..p.pos - 1]);
+                p.bump();
+                start = p.pos;
+            }
+            _ => {}
+        }
+    }
+    columns.push(&line[start..]);
+    columns
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+            self.bump();
+        }
+        &self.s[start..self.pos]
+    }
+
+    fn error(&self, reason: &str) -> FakerError {
+        FakerError::RuleSyntax {
+            rule: self.s.to_string(),
+            reason: format!("{} at column {}", reason, self.s[..self.pos].chars().count() + 1),
+        }
+    }
+
+    fn call(&mut self) -> Result<RuleCall, FakerError> {
+        let start = self.pos;
+        let name = self.identifier();
+        if name.is_empty() {
+            return Err(self.error("expected a rule name"));
+        }
+        self.skip_whitespace();
+        if self.bump() != Some('(') {
+            return Err(self.error("expected `(`"));
+        }
+        let mut args = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.bump();
+        } else {
+            loop {
+                args.push(self.argument()?);
+                match self.bump() {
+                    Some(',') => continue,
+                    Some(')') => break,
+                    _ => return Err(self.error("expected `,` or `)`")),
+                }
+            }
+        }
+        Ok(RuleCall {
+            name: name.to_string(),
+            args,
+            source: self.s[start..self.pos].to_string(),
+        })
+    }
+
+    fn argument(&mut self) -> Result<Argument, FakerError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if !self.identifier().is_empty() {
+            self.skip_whitespace();
+            if self.peek() == Some('(') {
+                self.pos = start;
+                let call = self.call()?;
+                self.skip_whitespace();
+                return Ok(Argument::Call(call));
+            }
+        }
+        self.pos = start;
+
+        let mut text = String::new();
+        let quoted = match self.peek().filter(|c| *c == '"' || *c == '\'') {
+            None => false,
+            Some(quote) => {
+                self.bump();
+                self.quoted(quote, &mut text)?;
+                true
+            }
+        };
+
+        // 引号外的部分到 , 或 ) 为止
+        let mut tail = String::new();
+        loop {
+            match self.peek() {
+                None | Some(',') | Some(')') => break,
+                Some('(') => return Err(self.error("unexpected `(`, quote values that contain parentheses")),
+                Some('"') | Some('\'') => return Err(self.error("unexpected quote")),
+                Some('\\') => {
+                    self.bump();
+                    self.escape(&mut tail, false)?;
+                }
+                Some(c) => {
+                    self.bump();
+                    tail.push(c);
+                }
+            }
+        }
+        text.push_str(if quoted { tail.trim() } else { tail.trim_end() });
+        Ok(Argument::Value { text, quoted })
+    }
+
+    /// 读到与开头的 quote 配对的引号为止，处理转义后的内容写入 out
+    fn quoted(&mut self, quote: char, out: &mut String) -> Result<(), FakerError> {
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('\\') => self.escape(out, true)?,
+                Some(c) if c == quote => return Ok(()),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    /// 处理 \ 之后的一个字符：\" \' \\ 去掉 \，引号外还有 \, \( \)；
+    /// 引号中的 \n \t \r 为换行、制表符和回车。其他字符保留 \，
+    /// 这样 regex("\d{3}") 和 enum_file(C:\data\names.txt) 这样的参数不需要写两个 \
+    fn escape(&mut self, out: &mut String, quoted: bool) -> Result<(), FakerError> {
+        match self.bump() {
+            None => return Err(self.error("unexpected end after `\\`")),
+            Some('n') if quoted => out.push('\n'),
+            Some('t') if quoted => out.push('\t'),
+            Some('r') if quoted => out.push('\r'),
+            Some(c @ ('"' | '\'' | '\\')) => out.push(c),
+            Some(c @ (',' | '(' | ')')) if !quoted => out.push(c),
+            Some(c) => {
+                out.push('\\');
+                out.push(c);
+            }
+        }
+        Ok(())
+    }
+}
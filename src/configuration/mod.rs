@@ -1,23 +1,53 @@
 use std::fs::read_to_string;
-use std::io::{Error, ErrorKind};
-use crate::configuration::definitions::{VariableType, GenerateRule, construct_from_str, OutPutType, OutPutTypeValue, VariableTypeValue};
-use crate::rules::IncreaseRule;
-use std::sync::{Mutex, Arc};
+use crate::configuration::definitions::{VariableType, GenerateRule, construct_from_str, OutPutType, OutPutTypeValue, VariableTypeValue, GenerateContext, DateFormat,
+                                      ColumnValue, RowValues, Tables,
+                                      DEFAULT_TIME_FORMAT, MAX_DECIMAL_PRECISION, decimal_to_string, uuid_to_string};
+use crate::configuration::expression::split_columns;
+use crate::error::FakerError;
 use lazy_static::lazy_static;
-use crate::configuration::definitions::OutPutTypeValue::Json;
 use std::collections::HashMap;
 use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use chrono::{DateTime, FixedOffset, NaiveTime, Offset, Utc};
+use std::fmt::Write;
 
 pub mod definitions;
+pub mod expression;
+pub mod schema;
+pub mod ddl;
 
+/// 类型后加 ? 而没有 null_rate 选项时为 null 的概率
+pub const DEFAULT_NULL_RATE: f64 = 0.1;
+
+/// is_null 与单元格的种子混合时使用的序号
+const NULL_STREAM: u64 = u64::MAX;
 
 /// 配置文件格式定义：
 /// 变量名||变量类型||数据制造规则
+/// 变量名||变量类型||数据制造规则||选项1=值1;选项2=值2
 /// '#'后的不会解析
 ///
-#[derive(Debug, Clone)]
+/// 变量类型后加 ? 表示该列可以为 null，默认 10% 的值为 null，例如 varchar?
+///
+/// 目前支持的选项：
+/// seed=INTEGER 该列单独使用的随机种子，不受全局种子和列顺序影响
+/// null_rate=FLOAT 每一格为 null 的概率，0 到 1 之间，大于 0 时该列可以为 null
+/// format=rfc3339|unix|unix_millis|strftime 格式 Date、Timestamp 和 Time 列的输出格式，
+///     默认分别为 %Y-%m-%d %H:%M:%S、rfc3339 和 %H:%M:%S，Time 列只支持 strftime 格式
+/// tz=UTC|+08:00 Date 和 Timestamp 列输出时使用的时区，默认 UTC
+#[derive(Debug)]
 pub struct FakerConfiguration {
-    pub items: Vec<ConfigurationItem>
+    pub items: Vec<ConfigurationItem>,
+    /// 全局随机种子，相同的种子和行号总是生成相同的一行
+    pub seed: u64,
+    /// generate() 下一次使用的行号
+    next_row: AtomicU64,
+    /// 各列生成的顺序，被引用的列排在引用它的列之前
+    order: Vec<usize>,
+    /// 每一列引用的列
+    dependencies: Vec<Vec<usize>>,
 }
 
 #[derive(Debug, Clone)]
@@ -25,167 +55,504 @@ pub struct ConfigurationItem {
     pub var_name: String,
     pub var_type: VariableType,
     pub rule: Box<dyn GenerateRule>,
+    /// 覆盖全局种子
+    pub seed: Option<u64>,
+    /// Date、Timestamp 和 Time 列的输出格式
+    pub date_format: DateFormat,
+    /// Date 和 Timestamp 列输出时使用的时区
+    pub time_zone: FixedOffset,
+    /// 该列是否可以为 null
+    pub nullable: bool,
+    /// 每一格为 null 的概率
+    pub null_rate: f64,
+}
+
+impl Clone for FakerConfiguration {
+    fn clone(&self) -> Self {
+        FakerConfiguration {
+            items: self.items.clone(),
+            seed: self.seed,
+            next_row: AtomicU64::new(self.next_row.load(Ordering::SeqCst)),
+            order: self.order.clone(),
+            dependencies: self.dependencies.clone(),
+        }
+    }
+}
+
+impl ConfigurationItem {
+    /// 按列的输出格式把值转换成 JSON，Decimal 输出为字符串以免丢失精度
+    fn render(&self, v: &VariableTypeValue) -> serde_json::Value {
+        match v {
+            VariableTypeValue::Integer(u) => json!(u),
+            VariableTypeValue::Float(u) => json!(u),
+            VariableTypeValue::String(u) => json!(u),
+            VariableTypeValue::Date(u) | VariableTypeValue::Timestamp(u) => self.date_format.format(u, &self.time_zone),
+            VariableTypeValue::Time(u) => match &self.date_format {
+                DateFormat::Pattern(p) => json!(u.format(p).to_string()),
+                _ => json!(u.format(DEFAULT_TIME_FORMAT).to_string()),
+            },
+            VariableTypeValue::Boolean(u) => json!(u),
+            VariableTypeValue::Decimal { value, scale } => json!(decimal_to_string(*value, *scale)),
+            VariableTypeValue::Bytes(u) => json!(u.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+            VariableTypeValue::Uuid(u) => json!(uuid_to_string(*u)),
+            VariableTypeValue::Null => serde_json::Value::Null,
+        }
+    }
 }
 
 impl FakerConfiguration {
-    pub fn read_from_file(path: &String) -> Result<FakerConfiguration, Error> {
-        let re = read_to_string(path)?;
-        let mut config = FakerConfiguration { items: vec![] };
-        for line in re.lines() {
-            if line.trim().starts_with("#") {
-                continue;
-            }
-            let split: Vec<&str> = line.split("||").collect();
+    pub fn read_from_file(path: &str) -> Result<FakerConfiguration, FakerError> {
+        let re = read_to_string(path).map_err(|e| FakerError::io(path, e))?;
+        FakerConfiguration::parse_lines(path, re.lines().enumerate().map(|(idx, line)| (idx + 1, line)), false)
+            .map_err(collect_errors)
+    }
 
-            if split.len() != 3 {
-                return Err(Error::new(ErrorKind::InvalidData, "输入格式解析错误"));
+    /// 解析配置文件中的若干行（行号从 1 开始），返回所有出错的行。
+    /// 只有 schema 文件中的表才有其他表可以引用，foreign_keys 为 false 时使用 fk 会报错
+    pub(crate) fn parse_lines<'a, I>(path: &str, lines: I, foreign_keys: bool) -> Result<FakerConfiguration, Vec<FakerError>>
+        where I: Iterator<Item = (usize, &'a str)>
+    {
+        let mut config = FakerConfiguration {
+            items: vec![],
+            seed: rand::random(),
+            next_row: AtomicU64::new(0),
+            order: vec![],
+            dependencies: vec![],
+        };
+        let mut errors = vec![];
+        // 每一列所在的行号
+        let mut columns_line = vec![];
+        for (line_number, line) in lines {
+            if line.trim().starts_with('#') || line.trim().is_empty() {
+                continue;
             }
-
-            let tt = match variable_type_from_str(split[1]) {
-                Ok(o) => {
-                    o
+            let parsed = parse_line(line).and_then(|it| match it.rule.foreign_keys().into_iter().next() {
+                Some((table, _)) if !foreign_keys => Err((Some(it.var_name), FakerError::UnknownTable(table))),
+                _ => Ok(it),
+            });
+            match parsed {
+                Ok(it) => {
+                    config.items.push(it);
+                    columns_line.push(line_number);
                 }
-                Err(_) => {
-                    return Err(Error::new(ErrorKind::InvalidData, "数据类型解析错误"));
+                Err((column, e)) => errors.push(FakerError::Config {
+                    path: path.to_string(),
+                    line: line_number,
+                    column,
+                    source: Box::new(e),
+                }),
+            }
+        }
+        // 有列解析失败时，引用它的列会被误报为引用了不存在的列
+        if errors.is_empty() {
+            match generation_order(&config.items) {
+                Ok((order, dependencies)) => {
+                    config.order = order;
+                    config.dependencies = dependencies;
                 }
-            };
+                Err((i, e)) => errors.push(FakerError::Config {
+                    path: path.to_string(),
+                    line: columns_line[i],
+                    column: Some(config.items[i].var_name.clone()),
+                    source: Box::new(e),
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
 
-            let rule = match construct_from_str(split[2]) {
-                Ok(o) => {
-                    o
-                }
-                Err(_) => {
-                    return Err(Error::new(ErrorKind::InvalidData, "构造规则解析错误"));
-                }
-            };
+    /// 所有列的变量名，按配置文件中的顺序
+    pub fn header(&self) -> Vec<String> {
+        self.items.iter().map(|n| n.var_name.clone()).collect()
+    }
 
-            let it = ConfigurationItem {
-                var_name: split[0].to_string(),
-                var_type: tt,
-                rule,
-            };
-            config.items.push(it);
-        }
-        Ok(config)
+    /// 生成下一行，行号由内部计数器按调用顺序分配
+    pub fn generate(&self, tt: OutPutType) -> Result<OutPutTypeValue, FakerError> {
+        let row = self.next_row.fetch_add(1, Ordering::SeqCst);
+        self.generate_row(tt, row)
     }
 
-    pub fn generate(&self, tt: OutPutType) -> Result<OutPutTypeValue, ()> {
-        return match tt {
-            OutPutType::Json => {
+    /// 生成第 row 行（从 0 开始），相同的种子和行号总是得到相同的结果
+    pub fn generate_row(&self, tt: OutPutType, row: u64) -> Result<OutPutTypeValue, FakerError> {
+        self.generate_row_in(tt, row, None)
+    }
+
+    /// 与 generate_row 相同，tables 为同一个 schema 中的其他表，供外键使用
+    pub fn generate_row_in(&self, tt: OutPutType, row: u64, tables: Option<&dyn Tables>) -> Result<OutPutTypeValue, FakerError> {
+        let values = self.generate_values(row, None, tables)?.into_iter().map(|v| v.unwrap());
+
+        match tt {
+            OutPutType::Json | OutPutType::JsonLines => {
                 let mut map = serde_json::Map::<String, serde_json::Value>::new();
 
-                for n in &self.items {
-                    match n.var_type {
-                        VariableType::Integer => {
-                            let v = n.rule.generate_into(VariableType::Integer)?;
-                            if let VariableTypeValue::Integer(u) = v {
-                                map.insert(n.var_name.clone(), json!(u));
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::Float => {
-                            let v = n.rule.generate_into(VariableType::Float)?;
-                            if let VariableTypeValue::Float(u) = v {
-                                map.insert(n.var_name.clone(), json!(u));
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::String => {
-                            let v = n.rule.generate_into(VariableType::String)?;
-                            if let VariableTypeValue::String(u) = v {
-                                map.insert(n.var_name.clone(), json!(u));
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::Date => {
-                            let v = n.rule.generate_into(VariableType::Date)?;
-                            if let VariableTypeValue::Date(u) = v {
-                                let str = u.format("%Y-%m-%d %H:%M:%S").to_string();
-                                map.insert(n.var_name.clone(), json!(str));
-                            } else {
-                                return Err(());
-                            }
-                        }
-                    }
+                for (n, v) in self.items.iter().zip(values) {
+                    map.insert(n.var_name.clone(), v.rendered);
                 }
 
                 Ok(OutPutTypeValue::Json(serde_json::Value::Object(map)))
             }
             OutPutType::Csv => {
-                let mut vec = Vec::<String>::new();
-
-                for n in &self.items {
-                    match n.var_type {
-                        VariableType::Integer => {
-                            let v = n.rule.generate_into(VariableType::Integer)?;
-                            if let VariableTypeValue::Integer(u) = v {
-                                vec.push(u.to_string());
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::Float => {
-                            let v = n.rule.generate_into(VariableType::Float)?;
-                            if let VariableTypeValue::Float(u) = v {
-                                vec.push(u.to_string());
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::String => {
-                            let v = n.rule.generate_into(VariableType::String)?;
-                            if let VariableTypeValue::String(u) = v {
-                                vec.push(u);
-                            } else {
-                                return Err(());
-                            }
-                        }
-                        VariableType::Date => {
-                            let v = n.rule.generate_into(VariableType::Date)?;
-                            if let VariableTypeValue::Date(u) = v {
-                                let str = u.format("%Y-%m-%d %H:%M:%S").to_string();
-                                vec.push(str);
-                            } else {
-                                return Err(());
-                            }
-                        }
+                Ok(OutPutTypeValue::Csv(values.map(|v| v.text()).collect()))
+            }
+            OutPutType::Sql { .. } | OutPutType::Parquet | OutPutType::Avro => Ok(OutPutTypeValue::Values(values.collect())),
+        }
+    }
+
+    /// 只生成第 row 行中名为 column 的列，其他表的外键从这里取值
+    pub fn generate_column(&self, column: &str, row: u64, tables: Option<&dyn Tables>) -> Result<ColumnValue, FakerError> {
+        let idx = self.items.iter().position(|n| n.var_name == column)
+            .ok_or_else(|| FakerError::UnknownColumn(column.to_string()))?;
+        Ok(self.generate_values(row, Some(idx), tables)?.swap_remove(idx).unwrap())
+    }
+
+    /// 按生成顺序生成第 row 行，only 不为 None 时只生成该列和它依赖的列，其余为 None
+    fn generate_values(&self, row: u64, only: Option<usize>, tables: Option<&dyn Tables>)
+                       -> Result<Vec<Option<ColumnValue>>, FakerError> {
+        let mut needed = vec![only.is_none(); self.items.len()];
+        if let Some(idx) = only {
+            // 依赖的列在生成顺序中总是排在前面，倒序遍历一次即可
+            needed[idx] = true;
+            for &i in self.order.iter().rev() {
+                if needed[i] {
+                    for &j in &self.dependencies[i] {
+                        needed[j] = true;
                     }
                 }
-                Ok(OutPutTypeValue::Csv(vec))
             }
+        }
+
+        let names: Vec<&str> = self.items.iter().map(|n| n.var_name.as_str()).collect();
+        let mut values: Vec<Option<ColumnValue>> = vec![None; self.items.len()];
+        for &i in self.order.iter().filter(|i| needed[**i]) {
+            let columns = RowValues {
+                names: &names,
+                values: &values,
+            };
+            let value = self.generate_value(i, row, columns, tables)?;
+            let rendered = self.items[i].render(&value);
+            values[i] = Some(ColumnValue { value, rendered });
+        }
+        Ok(values)
+    }
+
+    /// 生成第 column 列第 row 行的值，并检查规则返回的值与列的类型一致
+    fn generate_value(&self, column: usize, row: u64, columns: RowValues, tables: Option<&dyn Tables>)
+                      -> Result<VariableTypeValue, FakerError> {
+        let n = &self.items[column];
+        let seed = self.cell_seed(column, row);
+        if n.var_type == VariableType::Null || is_null(seed, n.null_rate) {
+            return Ok(VariableTypeValue::Null);
+        }
+        let ctx = &mut GenerateContext {
+            row,
+            rng: StdRng::seed_from_u64(seed),
+            columns,
+            tables,
+        };
+        let v = n.rule.generate_into(n.var_type.clone(), ctx)
+            .map_err(|e| FakerError::Column {
+                column: n.var_name.clone(),
+                source: Box::new(e),
+            })?;
+        if !v.matches(&n.var_type) {
+            return Err(FakerError::Column {
+                column: n.var_name.clone(),
+                source: Box::new(FakerError::unsupported_type(&format!("{:?}", n.rule), n.var_type.clone())),
+            });
+        }
+        Ok(v)
+    }
+
+    /// 第 column 列第 row 行的种子，随机数生成器只由种子、行号和列决定
+    fn cell_seed(&self, column: usize, row: u64) -> u64 {
+        match self.items[column].seed {
+            Some(s) => mix_seed(s, row),
+            None => mix_seed(mix_seed(self.seed, column as u64), row),
+        }
+    }
+}
+
+/// 解析配置文件中的一行，出错时同时返回出错的列名
+fn parse_line(line: &str) -> Result<ConfigurationItem, (Option<String>, FakerError)> {
+    let split = split_columns(line);
+
+    if split.len() != 3 && split.len() != 4 {
+        return Err((None, FakerError::Syntax(line.to_string())));
+    }
+    let var_name = split[0];
+    let located = |e: FakerError| (Some(var_name.to_string()), e);
+
+    // varchar? 表示该列可以为 null
+    let (type_str, nullable) = match split[1].trim().strip_suffix('?') {
+        Some(t) => (t, true),
+        None => (split[1], false),
+    };
+    let tt = variable_type_from_str(type_str).map_err(located)?;
+
+    let rule = construct_from_str(split[2]).map_err(located)?;
+    // Null 列总是输出 null，不会调用规则
+    if tt != VariableType::Null {
+        rule.check_type(&tt).map_err(located)?;
+    }
+
+    let mut it = ConfigurationItem {
+        var_name: var_name.to_string(),
+        var_type: tt.clone(),
+        rule,
+        seed: None,
+        date_format: DateFormat::default_for(&tt),
+        time_zone: Utc.fix(),
+        nullable: nullable || tt == VariableType::Null,
+        null_rate: if nullable { DEFAULT_NULL_RATE } else { 0.0 },
+    };
+    if split.len() == 4 {
+        apply_options(&mut it, split[3]).map_err(located)?;
+    }
+    Ok(it)
+}
+
+/// 按引用关系排列各列生成的顺序，被引用的列先生成，其余保持配置文件中的顺序。
+/// 同时返回每一列引用的列，引用了不存在的列、引用的列类型不对或者循环引用时返回出错的列
+#[allow(clippy::type_complexity)]
+fn generation_order(items: &[ConfigurationItem]) -> Result<(Vec<usize>, Vec<Vec<usize>>), (usize, FakerError)> {
+    let mut refs = vec![];
+    for (i, n) in items.iter().enumerate() {
+        let mut r = vec![];
+        for name in n.rule.references() {
+            match items.iter().position(|m| m.var_name == name) {
+                Some(j) => r.push(j),
+                None => return Err((i, FakerError::UnknownColumn(name))),
+            }
+        }
+        // Null 列总是输出 null，不会调用规则
+        if n.var_type != VariableType::Null {
+            let types: Vec<VariableType> = r.iter().map(|j| items[*j].var_type.clone()).collect();
+            n.rule.check_references(&types, &n.var_type).map_err(|e| (i, e))?;
+        }
+        refs.push(r);
+    }
+
+    /// 深度优先，path 为正在访问的列，再次遇到其中的列说明有循环
+    fn visit(i: usize, refs: &[Vec<usize>], done: &mut [bool], path: &mut Vec<usize>, order: &mut Vec<usize>)
+             -> Result<(), Vec<usize>> {
+        if done[i] {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|p| *p == i) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(i);
+            return Err(cycle);
+        }
+        path.push(i);
+        for &j in &refs[i] {
+            visit(j, refs, done, path, order)?;
+        }
+        path.pop();
+        done[i] = true;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut done = vec![false; items.len()];
+    let mut order = vec![];
+    for i in 0..items.len() {
+        visit(i, &refs, &mut done, &mut vec![], &mut order).map_err(|cycle| {
+            let names = cycle.iter().map(|c| items[*c].var_name.clone()).collect();
+            (cycle[0], FakerError::CyclicReference(names))
+        })?;
+    }
+    Ok((order, refs))
+}
+
+/// 读取配置时发现的所有错误，只有一个时直接返回
+fn collect_errors(mut errors: Vec<FakerError>) -> FakerError {
+    match errors.len() {
+        1 => errors.remove(0),
+        _ => FakerError::Multiple(errors),
+    }
+}
+
+/// 由单元格的种子决定这一格是否为 null，与规则使用的随机数互不影响，
+/// 因此改变 null_rate 不会改变其他格的值
+fn is_null(seed: u64, null_rate: f64) -> bool {
+    if null_rate <= 0.0 {
+        return false;
+    }
+    let r = (mix_seed(seed, NULL_STREAM) >> 11) as f64 / (1u64 << 53) as f64;
+    r < null_rate
+}
+
+/// splitmix64，把种子和一个序号混合成新的种子
+pub(crate) fn mix_seed(seed: u64, n: u64) -> u64 {
+    let mut z = seed.wrapping_add(n.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// 解析第四列的选项 key1=value1;key2=value2
+fn apply_options(item: &mut ConfigurationItem, s: &str) -> Result<(), FakerError> {
+    for option in s.split(';').map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        let (key, value) = match option.find('=') {
+            None => return Err(FakerError::InvalidOption(option.to_string())),
+            Some(idx) => (option[..idx].trim(), option[idx + 1..].trim()),
         };
+        match key {
+            "seed" => {
+                item.seed = Some(value.parse::<u64>().map_err(|_| FakerError::InvalidOption(option.to_string()))?);
+            }
+            "null_rate" => {
+                let rate = value.parse::<f64>().ok()
+                    .filter(|r| (0.0..=1.0).contains(r))
+                    .ok_or_else(|| FakerError::InvalidOption(option.to_string()))?;
+                item.null_rate = rate;
+                item.nullable = item.nullable || rate > 0.0;
+            }
+            "format" if is_date_type(&item.var_type) => {
+                item.date_format = match value {
+                    // Time 只有时分秒，只能用 strftime 格式
+                    "rfc3339" | "unix" | "unix_millis" if item.var_type == VariableType::Time => {
+                        return Err(FakerError::InvalidOption(option.to_string()));
+                    }
+                    "rfc3339" => DateFormat::Rfc3339,
+                    "unix" => DateFormat::UnixSeconds,
+                    "unix_millis" => DateFormat::UnixMillis,
+                    _ if !is_valid_pattern(value, &item.var_type) => {
+                        return Err(FakerError::InvalidOption(option.to_string()));
+                    }
+                    _ => DateFormat::Pattern(value.to_string()),
+                };
+            }
+            // Time 没有日期，不能换算时区
+            "tz" if matches!(item.var_type, VariableType::Date | VariableType::Timestamp) => {
+                item.time_zone = parse_time_zone(value).ok_or_else(|| FakerError::InvalidOption(option.to_string()))?;
+            }
+            _ => return Err(FakerError::InvalidOption(option.to_string())),
+        }
     }
+    Ok(())
+}
+
+/// 用样例时间试着格式化一次：格式错误或 Time 列用了 %Y 这样的日期格式时，
+/// chrono 在输出时才报错，to_string 会因此 panic
+fn is_valid_pattern(pattern: &str, var_type: &VariableType) -> bool {
+    let mut out = String::new();
+    if *var_type == VariableType::Time {
+        write!(out, "{}", NaiveTime::MIN.format(pattern)).is_ok()
+    } else {
+        write!(out, "{}", DateTime::<Utc>::UNIX_EPOCH.fixed_offset().format(pattern)).is_ok()
+    }
+}
+
+fn is_date_type(var_type: &VariableType) -> bool {
+    matches!(var_type, VariableType::Date | VariableType::Timestamp | VariableType::Time)
+}
+
+/// 解析 UTC、Z、+08:00、-0530 这样的时区
+fn parse_time_zone(s: &str) -> Option<FixedOffset> {
+    if s.eq_ignore_ascii_case("utc") || s == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match s.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours = digits[..2].parse::<i32>().ok()?;
+    let minutes = digits[2..].parse::<i32>().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
 }
 
 lazy_static! {
-    static ref VariableTypeMap : HashMap<&'static str,VariableType> = {
+    static ref VARIABLE_TYPE_MAP : HashMap<&'static str,VariableType> = {
         let mut m = HashMap::new();
-        m.insert("int",VariableType::Integer);
+        for name in &["int", "integer", "tinyint", "smallint", "mediumint", "bigint", "int2", "int4", "int8",
+            "serial", "smallserial", "bigserial", "serial2", "serial4", "serial8"] {
+            m.insert(*name, VariableType::Integer);
+        }
 
         m.insert("date",VariableType::Date);
+        m.insert("datetime",VariableType::Date);
+        m.insert("year",VariableType::Date);
 
-        m.insert("char",VariableType::String);
-        m.insert("varchar",VariableType::String);
-        m.insert("text",VariableType::String);
-        m.insert("enum",VariableType::String);
-        m.insert("string",VariableType::String);
+        for name in &["char", "varchar", "character", "character varying", "nchar", "nvarchar",
+            "text", "tinytext", "mediumtext", "longtext", "enum", "set", "string", "json", "jsonb"] {
+            m.insert(*name, VariableType::String);
+        }
+
+        for name in &["float", "float4", "float8", "real", "double", "double precision"] {
+            m.insert(*name, VariableType::Float);
+        }
+
+        m.insert("bool",VariableType::Boolean);
+        m.insert("boolean",VariableType::Boolean);
+        m.insert("bit",VariableType::Boolean);
 
-        m.insert("float",VariableType::Float);
+        m.insert("time",VariableType::Time);
+        m.insert("timestamp",VariableType::Timestamp);
+        m.insert("timestamptz",VariableType::Timestamp);
+        m.insert("timestamp with time zone",VariableType::Timestamp);
+
+        for name in &["binary", "varbinary", "blob", "tinyblob", "mediumblob", "longblob", "bytea", "bytes"] {
+            m.insert(*name, VariableType::Bytes);
+        }
+
+        m.insert("uuid",VariableType::Uuid);
+        m.insert("null",VariableType::Null);
         m
     };
 }
 
-pub fn variable_type_from_str(s: &str) -> Result<VariableType, ()> {
-    let op = VariableTypeMap.get(s);
-    return match op {
-        None => {
-            Err(())
-        }
-        Some(e) => {
-            Ok(e.clone())
+/// 解析变量类型，不区分大小写，支持 varchar(32)、decimal(10,2)、int unsigned 这样的写法。
+/// 除 decimal 外括号里的长度只做检查，不影响生成
+pub fn variable_type_from_str(s: &str) -> Result<VariableType, FakerError> {
+    let unknown = || FakerError::UnknownType(s.to_string());
+    let lower = s.trim().to_lowercase();
+    let (name, args) = match lower.find('(') {
+        None => (lower.as_str(), None),
+        Some(idx) => {
+            let rest = lower[idx + 1..].trim_end();
+            let args = rest.strip_suffix(')').ok_or_else(unknown)?;
+            let after = &lower[idx + 1 + rest.len()..];
+            if !after.trim().is_empty() {
+                return Err(unknown());
+            }
+            (&lower[..idx], Some(args))
         }
     };
-}
\ No newline at end of file
+    // 例如 int unsigned、timestamp with time zone
+    let name = name.split_whitespace()
+        .filter(|w| *w != "unsigned" && *w != "zerofill")
+        .collect::<Vec<_>>()
+        .join(" ");
+    let args = match args {
+        None => vec![],
+        Some(a) => a.split(',')
+            .map(|n| n.trim().parse::<u32>().map_err(|_| unknown()))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    if name == "decimal" || name == "numeric" {
+        let (precision, scale) = match args[..] {
+            [] => (10, 0),
+            [p] => (p, 0),
+            [p, s] => (p, s),
+            _ => return Err(unknown()),
+        };
+        if precision == 0 || precision > MAX_DECIMAL_PRECISION || scale > precision {
+            return Err(unknown());
+        }
+        return Ok(VariableType::Decimal { precision, scale });
+    }
+    if args.len() > 1 {
+        return Err(unknown());
+    }
+    VARIABLE_TYPE_MAP.get(name.as_str()).cloned().ok_or_else(unknown)
+}
@@ -0,0 +1,203 @@
+use std::fs::read_to_string;
+use crate::configuration::{FakerConfiguration, mix_seed, collect_errors};
+use crate::configuration::definitions::{ColumnValue, Tables};
+use crate::error::FakerError;
+
+/// 没有写 count 的表默认生成的行数
+pub const DEFAULT_TABLE_COUNT: u64 = 100;
+
+/// schema 文件格式定义：
+/// [表名]
+/// [表名] count=行数
+/// 变量名||变量类型||数据制造规则
+/// ...
+/// 每张表以 [表名] 开头，之后的列与配置文件的格式相同，'#'后的不会解析。
+/// 列可以用 fk(表名.列名) 引用其他表的列，被引用的表总是先生成
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub tables: Vec<Table>,
+    /// 没有写 count 的表生成的行数
+    pub default_count: u64,
+    /// 各表生成的顺序，父表在子表之前
+    order: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub name: String,
+    /// 该表生成的行数，None 时使用 Schema 的 default_count
+    pub count: Option<u64>,
+    pub config: FakerConfiguration,
+}
+
+/// 解析中的一张表：表名、行数、表头所在的行号和表中的行
+type Section<'a> = (String, Option<u64>, usize, Vec<(usize, &'a str)>);
+
+impl Schema {
+    pub fn read_from_file(path: &str) -> Result<Schema, FakerError> {
+        let re = read_to_string(path).map_err(|e| FakerError::io(path, e))?;
+        let located = |line: usize, column: Option<String>, e: FakerError| FakerError::Config {
+            path: path.to_string(),
+            line,
+            column,
+            source: Box::new(e),
+        };
+
+        let mut errors = vec![];
+        let mut sections: Vec<Section> = vec![];
+        for (idx, line) in re.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                match parse_header(trimmed) {
+                    Ok((name, _)) if sections.iter().any(|s| s.0 == name) => {
+                        errors.push(located(idx + 1, None, FakerError::DuplicateTable(name)));
+                    }
+                    Ok((name, count)) => sections.push((name, count, idx + 1, vec![])),
+                    Err(e) => errors.push(located(idx + 1, None, e)),
+                }
+            } else if let Some(section) = sections.last_mut() {
+                section.3.push((idx + 1, line));
+            } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                errors.push(located(idx + 1, None, FakerError::TableSyntax(line.to_string())));
+            }
+        }
+
+        let mut schema = Schema {
+            tables: vec![],
+            default_count: DEFAULT_TABLE_COUNT,
+            order: vec![],
+        };
+        let mut header_lines = vec![];
+        for (name, count, header_line, lines) in sections {
+            match FakerConfiguration::parse_lines(path, lines.into_iter(), true) {
+                Ok(config) => {
+                    schema.tables.push(Table { name, count, config });
+                    header_lines.push(header_line);
+                }
+                Err(e) => errors.extend(e),
+            }
+        }
+        // 有表解析失败时，引用它的外键会被误报为引用了不存在的表
+        if errors.is_empty() {
+            match table_order(&schema.tables) {
+                Ok(order) => schema.order = order,
+                Err((table, column, e)) => errors.push(located(header_lines[table], column, e)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(schema)
+        } else {
+            Err(collect_errors(errors))
+        }
+    }
+
+    /// 设置全局随机种子，每张表使用由它和表的序号混合出的种子
+    pub fn set_seed(&mut self, seed: u64) {
+        for (i, t) in self.tables.iter_mut().enumerate() {
+            t.config.seed = mix_seed(seed, i as u64);
+        }
+    }
+
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    /// 表生成的行数
+    pub fn count_of(&self, table: &Table) -> u64 {
+        table.count.unwrap_or(self.default_count)
+    }
+
+    /// 按生成的顺序排列的表，父表在子表之前
+    pub fn tables_in_order(&self) -> impl Iterator<Item = &Table> {
+        self.order.iter().map(move |i| &self.tables[*i])
+    }
+}
+
+impl Tables for Schema {
+    fn row_count(&self, table: &str) -> Option<u64> {
+        self.table(table).map(|t| self.count_of(t))
+    }
+
+    fn value(&self, table: &str, column: &str, row: u64) -> Result<ColumnValue, FakerError> {
+        let t = self.table(table).ok_or_else(|| FakerError::UnknownTable(table.to_string()))?;
+        t.config.generate_column(column, row, Some(self))
+    }
+}
+
+/// 解析 [users] 或 [users] count=100
+fn parse_header(line: &str) -> Result<(String, Option<u64>), FakerError> {
+    let syntax = || FakerError::TableSyntax(line.to_string());
+    let end = line.find(']').ok_or_else(syntax)?;
+    let name = line[1..end].trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        return Err(syntax());
+    }
+    let mut count = None;
+    for option in line[end + 1..].split(';').map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        match option.split_once('=') {
+            Some((key, value)) if key.trim() == "count" => {
+                count = Some(value.trim().parse::<u64>().map_err(|_| FakerError::InvalidOption(option.to_string()))?);
+            }
+            _ => return Err(FakerError::InvalidOption(option.to_string())),
+        }
+    }
+    Ok((name.to_string(), count))
+}
+
+/// 按外键排列各表生成的顺序，父表先生成，其余保持文件中的顺序。
+/// 出错时返回出错的表的序号和 表名.列名
+fn table_order(tables: &[Table]) -> Result<Vec<usize>, (usize, Option<String>, FakerError)> {
+    let mut parents = vec![];
+    for (i, t) in tables.iter().enumerate() {
+        let mut p = vec![];
+        for n in &t.config.items {
+            let column = Some(format!("{}.{}", t.name, n.var_name));
+            for (table, key) in n.rule.foreign_keys() {
+                let j = match tables.iter().position(|u| u.name == table) {
+                    Some(j) => j,
+                    None => return Err((i, column, FakerError::UnknownTable(table))),
+                };
+                // 同一张表中的外键会在生成父行时再次用到自己
+                if j == i {
+                    return Err((i, column, FakerError::CyclicForeignKey(vec![table.clone(), table])));
+                }
+                if !tables[j].config.items.iter().any(|m| m.var_name == key) {
+                    return Err((i, column, FakerError::UnknownColumn(format!("{}.{}", table, key))));
+                }
+                p.push(j);
+            }
+        }
+        parents.push(p);
+    }
+
+    /// 深度优先，path 为正在访问的表，再次遇到其中的表说明有循环
+    fn visit(i: usize, parents: &[Vec<usize>], done: &mut [bool], path: &mut Vec<usize>, order: &mut Vec<usize>)
+             -> Result<(), Vec<usize>> {
+        if done[i] {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|p| *p == i) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(i);
+            return Err(cycle);
+        }
+        path.push(i);
+        for &j in &parents[i] {
+            visit(j, parents, done, path, order)?;
+        }
+        path.pop();
+        done[i] = true;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut done = vec![false; tables.len()];
+    let mut order = vec![];
+    for i in 0..tables.len() {
+        visit(i, &parents, &mut done, &mut vec![], &mut order).map_err(|cycle| {
+            let names = cycle.iter().map(|c| tables[*c].name.clone()).collect();
+            (cycle[0], None, FakerError::CyclicForeignKey(names))
+        })?;
+    }
+    Ok(order)
+}
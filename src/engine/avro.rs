@@ -0,0 +1,152 @@
+use std::io::Write;
+use apache_avro::{Schema, Writer};
+use apache_avro::types::Value;
+use chrono::Timelike;
+use serde_json::json;
+use crate::configuration::{FakerConfiguration, mix_seed};
+use crate::configuration::definitions::{OutPutTypeValue, VariableType, VariableTypeValue, ColumnValue};
+use crate::engine::writer::RowWriter;
+use crate::error::FakerError;
+
+/// 缓存的行达到该数量时写出一个块
+pub const BLOCK_ROWS: usize = 1000;
+
+/// 生成同步标记时与种子混合的序号
+const SYNC_MARKER_STREAM: u64 = u64::MAX - 1;
+
+/// 输出 Avro object container 文件，schema 由各列的名字和类型生成，见 avro_schema
+pub struct AvroWriter<W: Write> {
+    writer: W,
+    schema: Schema,
+    /// (列名, 类型, 是否可以为 null)
+    columns: Vec<(String, VariableType, bool)>,
+    /// 每个块结尾的同步标记，由种子决定，相同的种子生成相同的文件
+    marker: [u8; 16],
+    /// 当前块中的行
+    block: Vec<Value>,
+    has_header: bool,
+}
+
+impl<W: Write> AvroWriter<W> {
+    pub fn new(writer: W, table: &str, config: &FakerConfiguration) -> Result<Self, FakerError> {
+        let schema = Schema::parse(&avro_schema(table, config))?;
+        let seed = mix_seed(config.seed, SYNC_MARKER_STREAM);
+        let mut marker = [0; 16];
+        marker[..8].copy_from_slice(&seed.to_le_bytes());
+        marker[8..].copy_from_slice(&mix_seed(seed, 0).to_le_bytes());
+        Ok(AvroWriter {
+            writer,
+            schema,
+            columns: config.items.iter().map(|n| (n.var_name.clone(), n.var_type.clone(), n.nullable)).collect(),
+            marker,
+            block: Vec::with_capacity(BLOCK_ROWS),
+            has_header: false,
+        })
+    }
+
+    /// 写出当前块，第一次调用时先写出文件头
+    fn write_block(&mut self) -> Result<(), FakerError> {
+        if self.has_header {
+            if !self.block.is_empty() {
+                Writer::append_to(&self.schema, &mut self.writer, self.marker).extend_from_slice(&self.block)?;
+            }
+        } else {
+            let mut w = Writer::builder().schema(&self.schema).writer(&mut self.writer).marker(self.marker).build();
+            w.extend_from_slice(&self.block)?;
+            w.into_inner()?;
+            self.has_header = true;
+        }
+        self.block.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> RowWriter for AvroWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Values(u) = row {
+            let mut fields = Vec::with_capacity(u.len());
+            for ((name, var_type, nullable), v) in self.columns.iter().zip(&u) {
+                let value = avro_value(v, var_type, *nullable).map_err(|e| FakerError::Column {
+                    column: name.clone(),
+                    source: Box::new(e),
+                })?;
+                fields.push((avro_name(name), value));
+            }
+            self.block.push(Value::Record(fields));
+            if self.block.len() >= BLOCK_ROWS {
+                self.write_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        self.write_block()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// 由各列生成 Avro 的 record schema，记录名为表名：
+/// Integer -> long，Float -> double，String -> string，Date 和 Timestamp -> long(timestamp-millis)，
+/// Time -> long(time-micros)，Boolean -> boolean，Decimal -> bytes(decimal)，Bytes -> bytes，
+/// Uuid -> string(uuid)，Null -> null；类型后有 ? 的列为 ["null", 类型] 的 union，默认值为 null。
+/// 名字中 Avro 不允许的字符换成 _
+pub fn avro_schema(table: &str, config: &FakerConfiguration) -> serde_json::Value {
+    let fields: Vec<serde_json::Value> = config.items.iter()
+        .map(|n| {
+            let t = match &n.var_type {
+                VariableType::Integer => json!("long"),
+                VariableType::Float => json!("double"),
+                VariableType::String => json!("string"),
+                VariableType::Date | VariableType::Timestamp => json!({"type": "long", "logicalType": "timestamp-millis"}),
+                VariableType::Time => json!({"type": "long", "logicalType": "time-micros"}),
+                VariableType::Boolean => json!("boolean"),
+                VariableType::Decimal { precision, scale } => {
+                    json!({"type": "bytes", "logicalType": "decimal", "precision": precision, "scale": scale})
+                }
+                VariableType::Bytes => json!("bytes"),
+                VariableType::Uuid => json!({"type": "string", "logicalType": "uuid"}),
+                VariableType::Null => json!("null"),
+            };
+            if n.nullable && n.var_type != VariableType::Null {
+                json!({"name": avro_name(&n.var_name), "type": ["null", t], "default": null})
+            } else {
+                json!({"name": avro_name(&n.var_name), "type": t})
+            }
+        })
+        .collect();
+    json!({"type": "record", "name": avro_name(table), "fields": fields})
+}
+
+/// Avro 的名字只能包含字母、数字和 _，且不能以数字开头
+fn avro_name(name: &str) -> String {
+    let mut out: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// 一列的值，可以为 null 的列包装成 union，Null 列的 schema 就是 null，不需要包装
+fn avro_value(v: &ColumnValue, var_type: &VariableType, nullable: bool) -> Result<Value, FakerError> {
+    let value = match &v.value {
+        VariableTypeValue::Integer(u) => Value::Long(*u),
+        VariableTypeValue::Float(u) => Value::Double(*u),
+        VariableTypeValue::String(u) => Value::String(u.clone()),
+        VariableTypeValue::Date(u) | VariableTypeValue::Timestamp(u) => Value::TimestampMillis(u.timestamp_millis()),
+        VariableTypeValue::Time(u) => {
+            Value::TimeMicros(u.num_seconds_from_midnight() as i64 * 1_000_000 + u.nanosecond() as i64 / 1_000)
+        }
+        VariableTypeValue::Boolean(u) => Value::Boolean(*u),
+        VariableTypeValue::Decimal { value, .. } => Value::Decimal(value.to_be_bytes().into()),
+        VariableTypeValue::Bytes(u) => Value::Bytes(u.clone()),
+        VariableTypeValue::Uuid(u) => Value::Uuid(apache_avro::Uuid::from_u128(*u)),
+        VariableTypeValue::Null if *var_type == VariableType::Null => return Ok(Value::Null),
+        VariableTypeValue::Null if nullable => return Ok(Value::Union(0, Box::new(Value::Null))),
+        VariableTypeValue::Null => return Err(FakerError::NotNullable),
+    };
+    Ok(if nullable { Value::Union(1, Box::new(value)) } else { value })
+}
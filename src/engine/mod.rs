@@ -1,100 +1,295 @@
 use crate::configuration::FakerConfiguration;
-use std::borrow::Borrow;
-use std::io::{Error, ErrorKind, Write};
+use std::io::{Write, BufWriter};
+use crate::error::FakerError;
 use crate::configuration::definitions::{OutPutType, OutPutTypeValue};
+use crate::engine::writer::{row_writer, CsvOptions};
+use crate::configuration::schema::Schema;
+use std::path::Path;
 use std::fs::File;
-use std::sync::{mpsc, Arc, Mutex, Barrier};
-use std::thread::Thread;
-use serde_json::Value;
+use std::sync::{mpsc, Mutex, Condvar};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::BTreeMap;
+
+pub mod writer;
+pub mod parquet;
+pub mod avro;
+
+/// 每次交给一个线程生成的行数
+const CHUNK_SIZE: u64 = 1024;
+/// 平均每个线程最多缓存的、还没有写出的块数
+const CHUNKS_PER_THREAD: u64 = 4;
 
 #[derive(Debug, Clone)]
 pub struct FakerEngine {
     thread_count: i32,
     config: FakerConfiguration,
+    csv_options: CsvOptions,
+    /// 是否按行号顺序输出
+    ordered: bool,
+    /// SQL 输出的表名和 Avro 的记录名，默认为配置文件名去掉扩展名
+    table: String,
 }
 
 impl FakerEngine {
-    pub fn new(path: &String, thread_count: i32) -> Result<Self, Error> {
+    pub fn new(path: &str, thread_count: i32) -> Result<Self, FakerError> {
         if thread_count <= 0 {
-            return Err(Error::new(ErrorKind::Other, "thread_count should > 0"));
+            return Err(FakerError::InvalidThreadCount(thread_count));
         }
         let config = FakerConfiguration::read_from_file(path)?;
+        let table = Path::new(path).file_stem().map_or("data".into(), |s| s.to_string_lossy().into_owned());
 
         Ok(FakerEngine {
             thread_count,
             config,
+            csv_options: CsvOptions::default(),
+            ordered: true,
+            table,
         })
     }
 
-    pub fn manufacturing_to_file(&self, tt: OutPutType, new_file_path: String, item_count: u64) -> Result<(), Error> {
-        match tt {
-            OutPutType::Json => {
-                let items_per_sub_thread = item_count / (self.thread_count as u64 - 1);
-                let items_main_thread = item_count % (self.thread_count as u64 - 1);
-
-                let mut file = File::create(new_file_path)?;
-
-                let mut v = Vec::<Value>::new();
-                let v_pt = Arc::new(Mutex::new(v));
-                //let barrier = Arc::new(Barrier::new());
-
-                crossbeam::thread::scope(|a| {
-                    for _ in 0..(self.thread_count - 1) {
-                        a.spawn(|_| {
-                            //let mut vec = vec![];
-                            for _ in 0..items_per_sub_thread {
-                                let js = self.config.generate(OutPutType::Json).unwrap();
-                                if let OutPutTypeValue::Json(u) = js {
-                                    let mut vec = v_pt.lock().unwrap();
-                                    vec.push(u);
-                                }
-                            }
-                        });
-                    }
-                    for _ in 0..items_main_thread {
-                        let js = self.config.generate(OutPutType::Json).unwrap();
-                        if let OutPutTypeValue::Json(u) = js {
-                            let mut vec = v_pt.lock().unwrap();
-                            vec.push(u);
-                        }
-                    }
-                });
+    /// 设置全局随机种子，相同的种子总是生成相同的数据
+    pub fn set_seed(&mut self, seed: u64) {
+        self.config.seed = seed;
+    }
 
-                let value = Value::Array(v_pt.lock().unwrap().clone());
+    /// 设置 CSV 输出的表头、分隔符、引号、换行和 null 的写法
+    pub fn set_csv_options(&mut self, csv_options: CsvOptions) {
+        self.csv_options = csv_options;
+    }
 
-                file.write(value.to_string().as_bytes());
+    /// 默认按行号顺序输出（相同的种子得到完全相同的文件）；
+    /// 设为 false 时每块生成完立即写出，不再等待前面的块，行的内容不变但顺序不确定
+    pub fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
 
-                Ok(())
-            }
-            OutPutType::Csv => {
-                let items_per_sub_thread = item_count / (self.thread_count as u64 - 1);
-                let items_main_thread = item_count % (self.thread_count as u64 - 1);
-
-                let mut file = File::create(new_file_path)?;
-                let mut wtr = csv::Writer::from_writer(file);
-                let w_pt = Arc::new(Mutex::new(wtr));
-
-                crossbeam::thread::scope(|a| {
-                    for _ in 0..(self.thread_count - 1){
-                        a.spawn(|_| {
-                            for _ in 0..items_per_sub_thread {
-                                let js = self.config.generate(OutPutType::Csv).unwrap();
-                                if let OutPutTypeValue::Csv(u) = js {
-                                    w_pt.lock().unwrap().write_record(u);
-                                }
-                            }
-                        });
+    /// 设置 SQL 输出的表名和 Avro 的记录名
+    pub fn set_table_name(&mut self, table: &str) {
+        self.table = table.to_string();
+    }
+
+    pub fn manufacturing_to_file(&self, tt: OutPutType, new_file_path: String, item_count: u64) -> Result<(), FakerError> {
+        let file = File::create(&new_file_path).map_err(|e| FakerError::io(&new_file_path, e))?;
+        self.manufacturing_to_writer(tt, BufWriter::new(file), item_count)
+    }
+
+    /// 与 manufacturing_to_file 相同，但输出到任意的 Write（例如 stdout）
+    pub fn manufacturing_to_writer<W: Write + Send>(&self, tt: OutPutType, writer: W, item_count: u64) -> Result<(), FakerError> {
+        let mut out = row_writer(&tt, writer, &self.table, &self.config, &self.csv_options)?;
+        self.generate_rows(tt, item_count, |row| out.write_row(row))?;
+        out.finish()
+    }
+
+    fn generate_rows<F>(&self, tt: OutPutType, item_count: u64, sink: F) -> Result<(), FakerError>
+        where F: FnMut(OutPutTypeValue) -> Result<(), FakerError>
+    {
+        generate_rows(self.thread_count, self.ordered, item_count, |row| self.config.generate_row(tt.clone(), row), sink)
+    }
+}
+
+/// 按 schema 文件生成多张表，每张表写到一个单独的输出中
+#[derive(Debug, Clone)]
+pub struct SchemaEngine {
+    thread_count: i32,
+    schema: Schema,
+    csv_options: CsvOptions,
+    ordered: bool,
+}
+
+impl SchemaEngine {
+    pub fn new(path: &str, thread_count: i32) -> Result<Self, FakerError> {
+        if thread_count <= 0 {
+            return Err(FakerError::InvalidThreadCount(thread_count));
+        }
+        let schema = Schema::read_from_file(path)?;
+
+        Ok(SchemaEngine {
+            thread_count,
+            schema,
+            csv_options: CsvOptions::default(),
+            ordered: true,
+        })
+    }
+
+    /// 设置全局随机种子，相同的种子总是生成相同的数据
+    pub fn set_seed(&mut self, seed: u64) {
+        self.schema.set_seed(seed);
+    }
+
+    /// 设置没有写 count 的表生成的行数
+    pub fn set_default_count(&mut self, count: u64) {
+        self.schema.default_count = count;
+    }
+
+    /// 设置 CSV 输出的表头、分隔符、引号、换行和 null 的写法
+    pub fn set_csv_options(&mut self, csv_options: CsvOptions) {
+        self.csv_options = csv_options;
+    }
+
+    /// 同 FakerEngine::set_ordered
+    pub fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
+
+    /// 按生成的顺序排列的表名，父表在子表之前
+    pub fn tables(&self) -> Vec<String> {
+        self.schema.tables_in_order().map(|t| t.name.clone()).collect()
+    }
+
+    /// 父表先生成，每张表写到 dir 中的 表名.json、表名.jsonl、表名.csv、表名.sql、表名.parquet 或 表名.avro
+    pub fn manufacturing_to_dir(&self, tt: OutPutType, dir: &str) -> Result<(), FakerError> {
+        std::fs::create_dir_all(dir).map_err(|e| FakerError::io(dir, e))?;
+        for table in self.tables() {
+            let path = Path::new(dir).join(format!("{}.{}", table, tt.extension()));
+            let path = path.to_string_lossy();
+            let file = File::create(path.as_ref()).map_err(|e| FakerError::io(&path, e))?;
+            self.manufacturing_table_to_writer(tt.clone(), &table, BufWriter::new(file))?;
+        }
+        Ok(())
+    }
+
+    /// 只生成一张表，输出到任意的 Write
+    pub fn manufacturing_table_to_writer<W: Write + Send>(&self, tt: OutPutType, table: &str, writer: W) -> Result<(), FakerError> {
+        let t = self.schema.table(table).ok_or_else(|| FakerError::UnknownTable(table.to_string()))?;
+        let mut out = row_writer(&tt, writer, &t.name, &t.config, &self.csv_options)?;
+        let generate = |row| t.config.generate_row_in(tt.clone(), row, Some(&self.schema));
+        generate_rows(self.thread_count, self.ordered, self.schema.count_of(t), generate, |row| out.write_row(row))?;
+        out.finish()
+    }
+}
+
+/// 行号按 CHUNK_SIZE 分块，放进一个共享的任务队列，每个线程生成完一块就领取下一块，
+/// 快的线程自然会多做一些。ordered 时当前线程把收到的块按块号重新排好后交给 sink，
+/// 因此输出只由种子决定，与线程的执行快慢无关。
+/// 线程最多领先写出端 thread_count * CHUNKS_PER_THREAD 块，内存占用与 item_count 无关
+fn generate_rows<G, F>(thread_count: i32, ordered: bool, item_count: u64, generate: G, mut sink: F) -> Result<(), FakerError>
+    where G: Fn(u64) -> Result<OutPutTypeValue, FakerError> + Sync,
+          F: FnMut(OutPutTypeValue) -> Result<(), FakerError>
+{
+    let chunk_count = item_count.div_ceil(CHUNK_SIZE);
+    let window = thread_count as u64 * CHUNKS_PER_THREAD;
+    let next_chunk = AtomicU64::new(0);
+    let progress = Progress::default();
+    let (tx, rx) = mpsc::channel();
+
+    crossbeam::thread::scope(|a| {
+        for _ in 0..thread_count {
+            let tx = tx.clone();
+            let (next_chunk, progress, generate) = (&next_chunk, &progress, &generate);
+            a.spawn(move |_| {
+                // 一个线程 panic 后缺少的块永远等不到，让其它线程和写出端都退出，再由 scope 传播 panic
+                let _guard = StopOnPanic(progress);
+                loop {
+                    let chunk = next_chunk.fetch_add(1, Ordering::SeqCst);
+                    if chunk >= chunk_count || !progress.wait_for(chunk, window) {
+                        break;
                     }
-                    for _ in 0..items_main_thread {
-                        let js = self.config.generate(OutPutType::Csv).unwrap();
-                        if let OutPutTypeValue::Csv(u) = js {
-                            w_pt.lock().unwrap().write_record(u);
-                        }
+                    let start = chunk * CHUNK_SIZE;
+                    let end = (start + CHUNK_SIZE).min(item_count);
+                    let rows: Result<Vec<_>, _> = (start..end)
+                        .map(generate)
+                        .collect();
+                    let failed = rows.is_err();
+                    // 发送失败说明写出端已经出错退出
+                    if tx.send((chunk, rows)).is_err() || failed {
+                        break;
                     }
-                });
+                }
+            });
+        }
+        drop(tx);
 
-                Ok(())
+        let mut write = || -> Result<(), FakerError> {
+            let mut pending = BTreeMap::new();
+            let mut written = 0;
+            while written < chunk_count {
+                // 接收失败说明生成线程 panic 了，所有线程都已退出，由 scope 负责传播
+                let (chunk, rows) = match rx.recv() {
+                    Ok(r) => r,
+                    Err(_) => break,
+                };
+                pending.insert(chunk, rows);
+                loop {
+                    let rows = if ordered {
+                        pending.remove(&written)
+                    } else {
+                        pending.pop_first().map(|(_, rows)| rows)
+                    };
+                    let rows = match rows {
+                        Some(rows) => rows,
+                        None => break,
+                    };
+                    for row in rows? {
+                        sink(row)?;
+                    }
+                    written += 1;
+                    progress.advance(written);
+                }
             }
+            Ok(())
+        };
+        let re = write();
+        progress.stop();
+        re
+    }).unwrap_or_else(|e| std::panic::resume_unwind(e))
+}
+
+/// 生成线程 panic 时通知其它线程停止，否则它们会一直等待写出端前进
+struct StopOnPanic<'a>(&'a Progress);
+
+impl Drop for StopOnPanic<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.stop();
+        }
+    }
+}
+
+/// 写出端的进度，生成线程据此控制领先的块数
+#[derive(Default)]
+struct Progress {
+    /// (已经写出的块数, 写出端是否已经退出)
+    state: Mutex<(u64, bool)>,
+    changed: Condvar,
+}
+
+impl Progress {
+    /// 等到第 chunk 块与已写出的块相差不到 window，写出端退出时返回 false
+    fn wait_for(&self, chunk: u64, window: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while chunk >= state.0 + window && !state.1 {
+            state = self.changed.wait(state).unwrap();
         }
+        !state.1
+    }
+
+    fn advance(&self, written: u64) {
+        self.state.lock().unwrap().0 = written;
+        self.changed.notify_all();
+    }
+
+    fn stop(&self) {
+        self.state.lock().unwrap().1 = true;
+        self.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    pub fn test_for_worker_panic() {
+        // 在另一个线程中运行，卡住时测试失败而不是一直等下去
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let re = std::panic::catch_unwind(|| {
+                let generate = |row| if row == 5000 { panic!("worker failed") } else { Ok(OutPutTypeValue::Csv(vec![])) };
+                generate_rows(4, true, 2_000_000, generate, |_| Ok(()))
+            });
+            tx.send(re.is_err()).unwrap();
+        });
+        assert_eq!(rx.recv_timeout(Duration::from_secs(60)), Ok(true));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,209 @@
+use std::io::Write;
+use std::sync::Arc;
+use chrono::Timelike;
+use parquet::basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::format::{MilliSeconds, MicroSeconds};
+use parquet::data_type::{ByteArray, FixedLenByteArray, DataType, BoolType, Int32Type, Int64Type, DoubleType,
+                         ByteArrayType, FixedLenByteArrayType};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedFileWriter, SerializedColumnWriter};
+use parquet::schema::types::Type;
+use crate::configuration::FakerConfiguration;
+use crate::configuration::definitions::{OutPutTypeValue, VariableType, VariableTypeValue, ColumnValue};
+use crate::engine::writer::RowWriter;
+use crate::error::FakerError;
+
+/// 每个 row group 的行数，攒够后立即写出并释放
+pub const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+/// decimal 的精度不超过该值时用 int64 保存，否则用 16 字节的定长数组
+const MAX_INT64_DECIMAL_PRECISION: u32 = 18;
+
+/// 输出 Parquet 文件，每列按类型写成对应的 Parquet 类型：
+/// Integer -> int64，Float -> double，String -> utf8，Date 和 Timestamp -> timestamp(毫秒, UTC)，
+/// Time -> time(微秒)，Boolean -> boolean，Decimal -> decimal，Bytes -> binary，Uuid -> uuid，
+/// 类型后有 ? 的列为 optional，其余为 required
+pub struct ParquetWriter<W: Write + Send> {
+    writer: SerializedFileWriter<W>,
+    columns: Vec<(String, VariableType, bool)>,
+    /// 当前 row group 中还没有写出的行
+    rows: Vec<Vec<ColumnValue>>,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(writer: W, config: &FakerConfiguration) -> Result<Self, FakerError> {
+        let columns: Vec<(String, VariableType, bool)> = config.items.iter()
+            .map(|n| (n.var_name.clone(), n.var_type.clone(), n.nullable))
+            .collect();
+        let fields = columns.iter()
+            .map(|(name, var_type, nullable)| parquet_type(name, var_type, *nullable).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let schema = Type::group_type_builder("schema").with_fields(fields).build()?;
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(ROW_GROUP_SIZE)
+            .build();
+        Ok(ParquetWriter {
+            writer: SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?,
+            columns,
+            rows: Vec::with_capacity(ROW_GROUP_SIZE),
+        })
+    }
+
+    fn write_row_group(&mut self) -> Result<(), FakerError> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let mut group = self.writer.next_row_group()?;
+        for (i, (name, var_type, nullable)) in self.columns.iter().enumerate() {
+            let mut column = group.next_column()?
+                .expect("the schema has a column for every item");
+            let column_error = |e| FakerError::Column {
+                column: name.clone(),
+                source: Box::new(e),
+            };
+            let rows = &self.rows;
+            match var_type {
+                VariableType::Integer => write_column::<Int64Type, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::Integer(u) => Some(*u),
+                    _ => None,
+                }),
+                VariableType::Float => write_column::<DoubleType, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::Float(u) => Some(*u),
+                    _ => None,
+                }),
+                VariableType::String => write_column::<ByteArrayType, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::String(u) => Some(ByteArray::from(u.as_str())),
+                    _ => None,
+                }),
+                VariableType::Date | VariableType::Timestamp => {
+                    write_column::<Int64Type, _>(&mut column, rows, i, *nullable, |v| match v {
+                        VariableTypeValue::Date(u) | VariableTypeValue::Timestamp(u) => Some(u.timestamp_millis()),
+                        _ => None,
+                    })
+                }
+                VariableType::Time => write_column::<Int64Type, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::Time(u) => {
+                        Some(u.num_seconds_from_midnight() as i64 * 1_000_000 + u.nanosecond() as i64 / 1_000)
+                    }
+                    _ => None,
+                }),
+                VariableType::Boolean => write_column::<BoolType, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::Boolean(u) => Some(*u),
+                    _ => None,
+                }),
+                VariableType::Decimal { precision, .. } if *precision <= MAX_INT64_DECIMAL_PRECISION => {
+                    write_column::<Int64Type, _>(&mut column, rows, i, *nullable, |v| match v {
+                        VariableTypeValue::Decimal { value, .. } => Some(*value as i64),
+                        _ => None,
+                    })
+                }
+                VariableType::Decimal { .. } => {
+                    write_column::<FixedLenByteArrayType, _>(&mut column, rows, i, *nullable, |v| match v {
+                        VariableTypeValue::Decimal { value, .. } => Some(FixedLenByteArray::from(value.to_be_bytes().to_vec())),
+                        _ => None,
+                    })
+                }
+                VariableType::Bytes => write_column::<ByteArrayType, _>(&mut column, rows, i, *nullable, |v| match v {
+                    VariableTypeValue::Bytes(u) => Some(ByteArray::from(u.clone())),
+                    _ => None,
+                }),
+                VariableType::Uuid => {
+                    write_column::<FixedLenByteArrayType, _>(&mut column, rows, i, *nullable, |v| match v {
+                        VariableTypeValue::Uuid(u) => Some(FixedLenByteArray::from(u.to_be_bytes().to_vec())),
+                        _ => None,
+                    })
+                }
+                VariableType::Null => write_column::<Int32Type, _>(&mut column, rows, i, true, |_| None),
+            }.map_err(column_error)?;
+            column.close()?;
+        }
+        group.close()?;
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> RowWriter for ParquetWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Values(u) = row {
+            self.rows.push(u);
+            if self.rows.len() >= ROW_GROUP_SIZE {
+                self.write_row_group()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        self.write_row_group()?;
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// 列在 Parquet schema 中的类型
+fn parquet_type(name: &str, var_type: &VariableType, nullable: bool) -> Result<Type, FakerError> {
+    let (physical, logical) = match var_type {
+        VariableType::Integer => (PhysicalType::INT64, None),
+        VariableType::Float => (PhysicalType::DOUBLE, None),
+        VariableType::String => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+        VariableType::Date | VariableType::Timestamp => (PhysicalType::INT64, Some(LogicalType::Timestamp {
+            is_adjusted_to_u_t_c: true,
+            unit: TimeUnit::MILLIS(MilliSeconds {}),
+        })),
+        VariableType::Time => (PhysicalType::INT64, Some(LogicalType::Time {
+            is_adjusted_to_u_t_c: false,
+            unit: TimeUnit::MICROS(MicroSeconds {}),
+        })),
+        VariableType::Boolean => (PhysicalType::BOOLEAN, None),
+        VariableType::Decimal { precision, scale } => {
+            let physical = if *precision <= MAX_INT64_DECIMAL_PRECISION {
+                PhysicalType::INT64
+            } else {
+                PhysicalType::FIXED_LEN_BYTE_ARRAY
+            };
+            (physical, Some(LogicalType::Decimal {
+                scale: *scale as i32,
+                precision: *precision as i32,
+            }))
+        }
+        VariableType::Bytes => (PhysicalType::BYTE_ARRAY, None),
+        VariableType::Uuid => (PhysicalType::FIXED_LEN_BYTE_ARRAY, Some(LogicalType::Uuid)),
+        VariableType::Null => (PhysicalType::INT32, Some(LogicalType::Unknown)),
+    };
+    let mut builder = Type::primitive_type_builder(name, physical)
+        .with_repetition(if nullable { Repetition::OPTIONAL } else { Repetition::REQUIRED })
+        .with_logical_type(logical);
+    if let VariableType::Decimal { precision, scale } = var_type {
+        builder = builder.with_precision(*precision as i32).with_scale(*scale as i32);
+    }
+    // uuid 和高精度的 decimal 都是 16 字节
+    if physical == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+        builder = builder.with_length(16);
+    }
+    Ok(builder.build()?)
+}
+
+/// 把 rows 中第 i 列的值写出，value 返回 None 的值为 null
+fn write_column<T, F>(column: &mut SerializedColumnWriter, rows: &[Vec<ColumnValue>], i: usize, nullable: bool, value: F)
+                      -> Result<(), FakerError>
+    where T: DataType,
+          F: Fn(&VariableTypeValue) -> Option<T::T>
+{
+    let mut values = Vec::with_capacity(rows.len());
+    let mut levels = Vec::with_capacity(rows.len());
+    for row in rows {
+        match value(&row[i].value) {
+            Some(v) => {
+                values.push(v);
+                levels.push(1);
+            }
+            None if nullable => levels.push(0),
+            // 例如 ref 引用了可以为 null 的列，而本列的类型后没有 ?
+            None => return Err(FakerError::NotNullable),
+        }
+    }
+    let levels = if nullable { Some(levels.as_slice()) } else { None };
+    column.typed::<T>().write_batch(&values, levels, None)?;
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use std::io::Write;
+use crate::configuration::definitions::{OutPutType, OutPutTypeValue, SqlDialect, ColumnValue, VariableTypeValue};
+use crate::configuration::FakerConfiguration;
+use crate::engine::parquet::ParquetWriter;
+use crate::engine::avro::AvroWriter;
+use crate::error::FakerError;
+
+/// 逐行写出生成的数据，不在内存中保留已经写出的行
+pub trait RowWriter {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError>;
+
+    /// 写出结尾（例如 JSON 数组的 `]`）并 flush
+    fn finish(&mut self) -> Result<(), FakerError>;
+}
+
+/// 按输出格式构造对应的 RowWriter，table 为表名，config 提供各列的变量名和类型
+pub fn row_writer<'a, W: Write + Send + 'a>(tt: &OutPutType, writer: W, table: &str, config: &FakerConfiguration, csv_options: &CsvOptions)
+                                            -> Result<Box<dyn RowWriter + 'a>, FakerError> {
+    let header = config.header();
+    Ok(match tt {
+        OutPutType::Json => Box::new(JsonArrayWriter::new(writer)),
+        OutPutType::JsonLines => Box::new(JsonLinesWriter { writer }),
+        OutPutType::Csv => Box::new(CsvWriter::new(writer, header, csv_options)?),
+        OutPutType::Sql { dialect, batch_size } => Box::new(SqlWriter::new(writer, *dialect, *batch_size, table, &header)),
+        OutPutType::Parquet => Box::new(ParquetWriter::new(writer, config)?),
+        OutPutType::Avro => Box::new(AvroWriter::new(writer, table, config)?),
+    })
+}
+
+/// 输出一个 JSON 数组 [{...},{...}]，每行写出后即丢弃
+pub struct JsonArrayWriter<W: Write> {
+    writer: W,
+    rows: u64,
+}
+
+impl<W: Write> JsonArrayWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonArrayWriter {
+            writer,
+            rows: 0,
+        }
+    }
+}
+
+impl<W: Write> RowWriter for JsonArrayWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Json(u) = row {
+            self.writer.write_all(if self.rows == 0 { b"[" } else { b"," })?;
+            serde_json::to_writer(&mut self.writer, &u).map_err(std::io::Error::from)?;
+            self.rows += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        self.writer.write_all(if self.rows == 0 { b"[]" } else { b"]" })?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// 输出 JSON Lines（NDJSON），每行一个 JSON 对象
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> RowWriter for JsonLinesWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Json(u) = row {
+            serde_json::to_writer(&mut self.writer, &u).map_err(std::io::Error::from)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// CSV 输出的选项
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// 是否在第一行写出列名
+    pub header: bool,
+    pub delimiter: u8,
+    pub quote_style: csv::QuoteStyle,
+    pub terminator: csv::Terminator,
+    /// null 写成的字符串
+    pub null: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            header: true,
+            delimiter: b',',
+            quote_style: csv::QuoteStyle::Necessary,
+            terminator: csv::Terminator::Any(b'\n'),
+            null: String::new(),
+        }
+    }
+}
+
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    null: String,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W, header: Vec<String>, options: &CsvOptions) -> Result<Self, FakerError> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .quote_style(options.quote_style)
+            .terminator(options.terminator)
+            .from_writer(writer);
+        if options.header {
+            writer.write_record(header)?;
+        }
+        Ok(CsvWriter {
+            writer,
+            null: options.null.clone(),
+        })
+    }
+}
+
+impl<W: Write> RowWriter for CsvWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Csv(u) = row {
+            let null = &self.null;
+            self.writer.write_record(u.iter().map(|v| v.as_ref().unwrap_or(null)))?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// 输出 INSERT INTO 表 (列, ...) VALUES (...), (...); 每条语句最多 batch_size 行
+pub struct SqlWriter<W: Write> {
+    writer: W,
+    dialect: SqlDialect,
+    batch_size: usize,
+    /// INSERT INTO ... VALUES
+    insert: String,
+    /// 当前语句中已经写出的行数
+    rows: usize,
+}
+
+impl<W: Write> SqlWriter<W> {
+    pub fn new(writer: W, dialect: SqlDialect, batch_size: usize, table: &str, header: &[String]) -> Self {
+        let columns: Vec<String> = header.iter().map(|h| quote_identifier(dialect, h)).collect();
+        SqlWriter {
+            writer,
+            dialect,
+            batch_size: batch_size.max(1),
+            insert: format!("INSERT INTO {} ({}) VALUES", quote_identifier(dialect, table), columns.join(", ")),
+            rows: 0,
+        }
+    }
+}
+
+impl<W: Write> RowWriter for SqlWriter<W> {
+    fn write_row(&mut self, row: OutPutTypeValue) -> Result<(), FakerError> {
+        if let OutPutTypeValue::Values(u) = row {
+            if self.rows == 0 {
+                writeln!(self.writer, "{}", self.insert)?;
+            } else {
+                self.writer.write_all(b",\n")?;
+            }
+            let values: Vec<String> = u.iter().map(|v| sql_literal(self.dialect, v)).collect();
+            write!(self.writer, "({})", values.join(", "))?;
+            self.rows += 1;
+            if self.rows == self.batch_size {
+                self.writer.write_all(b";\n")?;
+                self.rows = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), FakerError> {
+        if self.rows > 0 {
+            self.writer.write_all(b";\n")?;
+            self.rows = 0;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// MySQL 用反引号，其他方言用双引号，引号本身写两次
+pub fn quote_identifier(dialect: SqlDialect, name: &str) -> String {
+    let quote = if dialect == SqlDialect::MySql { '`' } else { '"' };
+    let mut out = String::with_capacity(name.len() + 2);
+    out.push(quote);
+    for c in name.chars() {
+        if c == quote {
+            out.push(quote);
+        }
+        out.push(c);
+    }
+    out.push(quote);
+    out
+}
+
+/// 单引号写两次；MySQL 默认把反斜杠当作转义字符，因此还要转义反斜杠和 \0
+pub fn quote_string(dialect: SqlDialect, s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('\'');
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("''"),
+            '\\' if dialect == SqlDialect::MySql => out.push_str("\\\\"),
+            '\0' if dialect == SqlDialect::MySql => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// 一列的值在 SQL 中的字面量，时间按列的 format 输出
+fn sql_literal(dialect: SqlDialect, v: &ColumnValue) -> String {
+    match (&v.value, &v.rendered) {
+        (_, serde_json::Value::Null) => "NULL".to_string(),
+        (VariableTypeValue::Boolean(b), _) => match (dialect, b) {
+            (SqlDialect::Sqlite, b) => (*b as u8).to_string(),
+            (_, true) => "TRUE".to_string(),
+            (_, false) => "FALSE".to_string(),
+        },
+        (VariableTypeValue::Bytes(b), _) => {
+            let hex: String = b.iter().map(|b| format!("{:02x}", b)).collect();
+            match dialect {
+                SqlDialect::PostgreSql => format!("'\\x{}'", hex),
+                _ => format!("X'{}'", hex),
+            }
+        }
+        (VariableTypeValue::Decimal { .. }, serde_json::Value::String(s)) => s.clone(),
+        (_, serde_json::Value::String(s)) => quote_string(dialect, s),
+        (_, r) => r.to_string(),
+    }
+}
@@ -0,0 +1,233 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use crate::configuration::definitions::VariableType;
+
+/// 规则层、配置层和引擎共用的错误类型
+#[derive(Debug)]
+pub enum FakerError {
+    /// 读写文件失败，path 为 None 时表示输出流
+    Io {
+        path: Option<String>,
+        source: io::Error,
+    },
+    /// 配置行不符合 变量名||变量类型||数据制造规则 的格式
+    Syntax(String),
+    /// 规则不符合 foo(var1,var2,...) 的格式，reason 说明出错的位置
+    RuleSyntax {
+        rule: String,
+        reason: String,
+    },
+    /// 未知的变量类型
+    UnknownType(String),
+    /// 未知的生成规则
+    UnknownRule(String),
+    /// 规则引用了不存在的列
+    UnknownColumn(String),
+    /// 列之间循环引用，依次为循环中的列名
+    CyclicReference(Vec<String>),
+    /// 外键引用了不存在的表，或者在只有一张表的配置中使用外键
+    UnknownTable(String),
+    /// 表之间通过外键循环引用，依次为循环中的表名
+    CyclicForeignKey(Vec<String>),
+    /// schema 文件中的表头不符合 [表名] 或 [表名] count=行数 的格式
+    TableSyntax(String),
+    /// schema 文件中重复的表名
+    DuplicateTable(String),
+    /// 无法解析的 CREATE TABLE 语句，说明出错的原因
+    DdlSyntax(String),
+    /// 规则的参数个数不对
+    ArgumentCount {
+        rule: String,
+        expected: String,
+        found: usize,
+    },
+    /// 规则的某个参数无法解析
+    InvalidArgument {
+        rule: String,
+        arg: String,
+        cause: Option<Box<dyn Error + Send + Sync>>,
+    },
+    /// 第四列的选项无法解析
+    InvalidOption(String),
+    /// 规则不能生成该类型的数据，例如 increase 生成 float
+    UnsupportedType {
+        rule: String,
+        var_type: VariableType,
+    },
+    /// 规则产生的值无法转换为该列的类型，例如 int 列 enum 出了 abc
+    InvalidValue {
+        rule: String,
+        value: String,
+        var_type: VariableType,
+        cause: Option<Box<dyn Error + Send + Sync>>,
+    },
+    /// 数列在第 row 行超出了 i64 的范围
+    Overflow {
+        rule: String,
+        row: u64,
+    },
+    /// 类型后没有 ? 的列生成了 null，无法写进 required 的列
+    NotNullable,
+    /// 引擎的线程数不合法
+    InvalidThreadCount(i32),
+    /// 带上配置文件、行号和列名的错误
+    Config {
+        path: String,
+        line: usize,
+        column: Option<String>,
+        source: Box<FakerError>,
+    },
+    /// 生成某一列时出现的错误
+    Column {
+        column: String,
+        source: Box<FakerError>,
+    },
+    /// 读取配置时发现的多个错误
+    Multiple(Vec<FakerError>),
+}
+
+impl FakerError {
+    pub fn io(path: &str, source: io::Error) -> Self {
+        FakerError::Io {
+            path: Some(path.to_string()),
+            source,
+        }
+    }
+
+    pub fn invalid_argument<E>(rule: &str, arg: &str, cause: E) -> Self
+        where E: Into<Box<dyn Error + Send + Sync>>
+    {
+        FakerError::InvalidArgument {
+            rule: rule.to_string(),
+            arg: arg.to_string(),
+            cause: Some(cause.into()),
+        }
+    }
+
+    pub fn unsupported_type(rule: &str, var_type: VariableType) -> Self {
+        FakerError::UnsupportedType {
+            rule: rule.to_string(),
+            var_type,
+        }
+    }
+
+    pub fn invalid_value<E>(rule: &str, value: &str, var_type: VariableType, cause: E) -> Self
+        where E: Into<Box<dyn Error + Send + Sync>>
+    {
+        FakerError::InvalidValue {
+            rule: rule.to_string(),
+            value: value.to_string(),
+            var_type,
+            cause: Some(cause.into()),
+        }
+    }
+}
+
+impl fmt::Display for FakerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FakerError::Io { path: Some(path), source } => write!(f, "{}: {}", path, source),
+            FakerError::Io { path: None, source } => write!(f, "{}", source),
+            FakerError::Syntax(line) => {
+                write!(f, "expected `name||type||rule` or `name||type||rule||options`, found `{}`", line)
+            }
+            FakerError::RuleSyntax { rule, reason } => {
+                write!(f, "expected `rule(arg1,arg2,...)`, found `{}`: {}", rule, reason)
+            }
+            FakerError::UnknownType(t) => write!(f, "unknown type `{}`", t),
+            FakerError::UnknownRule(r) => write!(f, "unknown rule `{}`", r),
+            FakerError::UnknownColumn(c) => write!(f, "unknown column `{}`", c),
+            FakerError::CyclicReference(names) => {
+                write!(f, "columns refer to each other: `{}`", names.join("` -> `"))
+            }
+            FakerError::UnknownTable(t) => write!(f, "unknown table `{}`", t),
+            FakerError::CyclicForeignKey(names) => {
+                write!(f, "tables refer to each other: `{}`", names.join("` -> `"))
+            }
+            FakerError::TableSyntax(line) => {
+                write!(f, "expected `[table]` or `[table] count=N`, found `{}`", line)
+            }
+            FakerError::DuplicateTable(t) => write!(f, "table `{}` is defined more than once", t),
+            FakerError::DdlSyntax(reason) => write!(f, "invalid CREATE TABLE statement: {}", reason),
+            FakerError::ArgumentCount { rule, expected, found } => {
+                write!(f, "`{}` expects {} argument(s), found {}", rule, expected, found)
+            }
+            FakerError::InvalidArgument { rule, arg, cause } => {
+                write!(f, "invalid argument `{}` for `{}`", arg, rule)?;
+                if let Some(c) = cause {
+                    write!(f, ": {}", c)?;
+                }
+                Ok(())
+            }
+            FakerError::InvalidOption(o) => write!(f, "invalid option `{}`", o),
+            FakerError::UnsupportedType { rule, var_type } => {
+                write!(f, "`{}` cannot generate {:?}", rule, var_type)
+            }
+            FakerError::InvalidValue { rule, value, var_type, cause } => {
+                write!(f, "`{}` value `{}` is not a valid {:?}", rule, value, var_type)?;
+                if let Some(c) = cause {
+                    write!(f, ": {}", c)?;
+                }
+                Ok(())
+            }
+            FakerError::Overflow { rule, row } => write!(f, "`{}` overflowed i64 at row {}", rule, row),
+            FakerError::NotNullable => write!(f, "null value in a column without `?` after its type"),
+            FakerError::InvalidThreadCount(n) => write!(f, "thread count should be > 0, found {}", n),
+            FakerError::Config { path, line, column: Some(column), source } => {
+                write!(f, "{}:{}: column `{}`: {}", path, line, column, source)
+            }
+            FakerError::Config { path, line, column: None, source } => {
+                write!(f, "{}:{}: {}", path, line, source)
+            }
+            FakerError::Column { column, source } => write!(f, "column `{}`: {}", column, source),
+            FakerError::Multiple(errors) => {
+                write!(f, "{} errors found", errors.len())?;
+                for e in errors {
+                    write!(f, "\n  {}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for FakerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FakerError::Io { source, .. } => Some(source),
+            FakerError::InvalidArgument { cause: Some(c), .. } => Some(c.as_ref()),
+            FakerError::InvalidValue { cause: Some(c), .. } => Some(c.as_ref()),
+            FakerError::Config { source, .. } => Some(source.as_ref()),
+            FakerError::Column { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FakerError {
+    fn from(e: io::Error) -> Self {
+        FakerError::Io {
+            path: None,
+            source: e,
+        }
+    }
+}
+
+impl From<csv::Error> for FakerError {
+    fn from(e: csv::Error) -> Self {
+        FakerError::from(io::Error::from(e))
+    }
+}
+
+impl From<apache_avro::Error> for FakerError {
+    fn from(e: apache_avro::Error) -> Self {
+        FakerError::from(io::Error::other(e))
+    }
+}
+
+impl From<parquet::errors::ParquetError> for FakerError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        FakerError::from(io::Error::other(e))
+    }
+}
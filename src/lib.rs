@@ -1,3 +1,4 @@
 pub mod rules;
 pub mod engine;
-pub mod configuration;
\ No newline at end of file
+pub mod configuration;
+pub mod error;
@@ -1,46 +1,357 @@
-use data_faker_rust::rules::IncreaseRule;
-use std::any::{Any, TypeId};
-use data_faker_rust::configuration::{FakerConfiguration, ConfigurationItem};
-use std::borrow::Borrow;
-use data_faker_rust::configuration::definitions::VariableType;
-use std::sync::Arc;
-use std::cell::RefCell;
-use std::ops::Deref;
-use crossbeam::thread;
+use data_faker_rust::configuration::definitions::{OutPutType, SqlDialect};
+use data_faker_rust::engine::{FakerEngine, SchemaEngine};
+use data_faker_rust::engine::writer::CsvOptions;
+use csv::{QuoteStyle, Terminator};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use data_faker_rust::configuration::FakerConfiguration;
+use data_faker_rust::configuration::schema::Schema;
+use data_faker_rust::configuration::ddl::starter_config;
+use data_faker_rust::error::FakerError;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+/// 配置文件解析失败时的退出码
+const EXIT_CONFIG_ERROR: i32 = 2;
+/// 生成或写出数据失败时的退出码
+const EXIT_GENERATE_ERROR: i32 = 1;
 
 fn main() {
-    let config = FakerConfiguration::read_from_file(String::from("test.txt").borrow()).unwrap();
+    let default_threads = std::thread::available_parallelism().map_or(1, |n| n.get()).to_string();
+    let matches = App::new("data-faker")
+        .about("A tool for manufacturing test data")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("generate")
+            .about("Generate data from a configuration file")
+            .arg(Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Configuration file, one `name||type||rule` per line")
+                .required_unless("schema"))
+            .arg(Arg::with_name("schema")
+                .long("schema")
+                .value_name("FILE")
+                .help("Schema file with several `[table]` sections, writes one file per table into the output directory")
+                .conflicts_with("config")
+                .requires("output"))
+            .arg(Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["json", "jsonl", "ndjson", "csv", "sql", "parquet", "avro"])
+                .default_value("csv"))
+            .arg(Arg::with_name("count")
+                .short("n")
+                .long("count")
+                .value_name("N")
+                .help("Number of rows to generate, or of each table without `count=N` in a schema")
+                .default_value("100"))
+            .arg(Arg::with_name("threads")
+                .short("t")
+                .long("threads")
+                .value_name("N")
+                .help("Number of generating threads, defaults to the number of CPUs")
+                .default_value(&default_threads))
+            .arg(Arg::with_name("no-header")
+                .long("no-header")
+                .help("Do not write the column names as the first CSV row"))
+            .arg(Arg::with_name("delimiter")
+                .long("delimiter")
+                .value_name("CHAR")
+                .help("CSV field delimiter, `\\t` for tab")
+                .default_value(","))
+            .arg(Arg::with_name("quote")
+                .long("quote")
+                .value_name("STYLE")
+                .help("When to quote CSV fields")
+                .possible_values(&["necessary", "always", "non-numeric", "never"])
+                .default_value("necessary"))
+            .arg(Arg::with_name("line-terminator")
+                .long("line-terminator")
+                .value_name("TERMINATOR")
+                .possible_values(&["lf", "crlf"])
+                .default_value("lf"))
+            .arg(Arg::with_name("null")
+                .long("null")
+                .value_name("STR")
+                .help("How null is written in CSV, an empty cell by default")
+                .default_value(""))
+            .arg(Arg::with_name("dialect")
+                .long("dialect")
+                .value_name("DIALECT")
+                .help("SQL dialect of the INSERT statements")
+                .possible_values(&["mysql", "postgresql", "postgres", "sqlite"])
+                .default_value("mysql"))
+            .arg(Arg::with_name("batch-size")
+                .long("batch-size")
+                .value_name("N")
+                .help("Maximum number of rows in one INSERT statement")
+                .default_value("1000"))
+            .arg(Arg::with_name("table")
+                .long("table")
+                .value_name("NAME")
+                .help("Table name of the INSERT statements and Avro record name, defaults to the configuration file name")
+                .conflicts_with("schema"))
+            .arg(Arg::with_name("unordered")
+                .long("unordered")
+                .help("Write rows as soon as they are generated instead of in row order"))
+            .arg(Arg::with_name("seed")
+                .short("s")
+                .long("seed")
+                .value_name("SEED")
+                .help("Random seed, the same seed always produces the same data"))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Output file, writes to stdout if not given; the output directory with --schema")))
+        .subcommand(SubCommand::with_name("schema")
+            .about("Print CREATE TABLE statements for the columns of a configuration or schema file")
+            .arg(Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Configuration file, one `name||type||rule` per line")
+                .required_unless("schema"))
+            .arg(Arg::with_name("schema")
+                .long("schema")
+                .value_name("FILE")
+                .help("Schema file with several `[table]` sections")
+                .conflicts_with("config"))
+            .arg(Arg::with_name("dialect")
+                .long("dialect")
+                .value_name("DIALECT")
+                .possible_values(&["mysql", "postgresql", "postgres", "sqlite"])
+                .default_value("mysql"))
+            .arg(Arg::with_name("table")
+                .long("table")
+                .value_name("NAME")
+                .help("Table name, defaults to the configuration file name")
+                .conflicts_with("schema"))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Output file, writes to stdout if not given")))
+        .subcommand(SubCommand::with_name("import")
+            .about("Write a starter configuration file from CREATE TABLE statements")
+            .arg(Arg::with_name("ddl")
+                .value_name("FILE")
+                .help("SQL file with one or more CREATE TABLE statements, several tables produce a schema file")
+                .required(true))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Output file, writes to stdout if not given")))
+        .get_matches();
 
-    let s = Arc::new(config.items[2].borrow());
+    match matches.subcommand() {
+        ("generate", Some(sub)) => generate(sub),
+        ("schema", Some(sub)) => schema(sub),
+        ("import", Some(sub)) => import(sub),
+        _ => {}
+    }
+}
 
-    thread::scope(|a| {
-        let mut vec = vec![];
-        for z in 0..3 {
-            let s_clone = s.clone();
-            let x = a.spawn(move |_| {
-                for _ in 1..100 {
-                    let re = s_clone.rule.generate_into(VariableType::String).unwrap();
-                    println!("{}:{:?}", z, re);
-                }
-            });
-            vec.push(x);
+fn generate(args: &ArgMatches) {
+    let count = parse_number::<u64>(args, "count");
+    let threads = parse_number::<i32>(args, "threads");
+    let tt = match args.value_of("format") {
+        Some("json") => OutPutType::Json,
+        Some("jsonl") | Some("ndjson") => OutPutType::JsonLines,
+        Some("parquet") => OutPutType::Parquet,
+        Some("avro") => OutPutType::Avro,
+        Some("sql") => OutPutType::Sql {
+            dialect: sql_dialect(args),
+            batch_size: match parse_number::<usize>(args, "batch-size") {
+                0 => clap::Error::value_validation_auto("--batch-size must be at least 1".to_string()).exit(),
+                n => n,
+            },
+        },
+        _ => OutPutType::Csv,
+    };
+
+    if let Some(schema) = args.value_of("schema") {
+        generate_schema(args, schema, tt, count, threads);
+        return;
+    }
+
+    let config = args.value_of("config").unwrap();
+    let mut engine = match FakerEngine::new(config, threads) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(EXIT_CONFIG_ERROR);
+        }
+    };
+
+    engine.set_csv_options(csv_options(args));
+    engine.set_ordered(!args.is_present("unordered"));
+
+    if args.is_present("seed") {
+        engine.set_seed(parse_number::<u64>(args, "seed"));
+    }
+    if let Some(table) = args.value_of("table") {
+        engine.set_table_name(table);
+    }
+
+    let re = match args.value_of("output") {
+        Some(path) => engine.manufacturing_to_file(tt, path.to_string(), count),
+        None => engine.manufacturing_to_writer(tt, io::stdout(), count),
+    };
+    if let Err(e) = re {
+        eprintln!("error: {}", e);
+        process::exit(EXIT_GENERATE_ERROR);
+    }
+}
+
+fn generate_schema(args: &ArgMatches, schema: &str, tt: OutPutType, count: u64, threads: i32) {
+    let mut engine = match SchemaEngine::new(schema, threads) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(EXIT_CONFIG_ERROR);
         }
-        for i in vec {
-            i.join();
+    };
+
+    engine.set_default_count(count);
+    engine.set_csv_options(csv_options(args));
+    engine.set_ordered(!args.is_present("unordered"));
+
+    if args.is_present("seed") {
+        engine.set_seed(parse_number::<u64>(args, "seed"));
+    }
+
+    if let Err(e) = engine.manufacturing_to_dir(tt, args.value_of("output").unwrap()) {
+        eprintln!("error: {}", e);
+        process::exit(EXIT_GENERATE_ERROR);
+    }
+}
+
+fn schema(args: &ArgMatches) {
+    let dialect = sql_dialect(args);
+    let ddl = match args.value_of("schema") {
+        Some(path) => Schema::read_from_file(path).map(|s| s.create_tables(dialect)),
+        None => {
+            let path = args.value_of("config").unwrap();
+            let table = args.value_of("table").map_or_else(
+                || Path::new(path).file_stem().map_or("data".into(), |s| s.to_string_lossy().into_owned()),
+                |t| t.to_string());
+            FakerConfiguration::read_from_file(path).map(|c| c.create_table(&table, dialect))
+        }
+    };
+    let ddl = match ddl {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(EXIT_CONFIG_ERROR);
         }
-    }).unwrap();
+    };
+
+    write_text(args, &ddl);
+}
+
+fn import(args: &ArgMatches) {
+    let path = args.value_of("ddl").unwrap();
+    let config = std::fs::read_to_string(path)
+        .map_err(|e| FakerError::io(path, e))
+        .and_then(|sql| starter_config(&sql));
+    match config {
+        Ok(c) => write_text(args, &c),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(EXIT_CONFIG_ERROR);
+        }
+    }
+}
+
+/// 写到 --output 指定的文件，没有时写到 stdout
+fn write_text(args: &ArgMatches, text: &str) {
+    let re = match args.value_of("output") {
+        Some(path) => std::fs::write(path, text),
+        None => io::stdout().write_all(text.as_bytes()),
+    };
+    if let Err(e) = re {
+        eprintln!("error: {}", e);
+        process::exit(EXIT_GENERATE_ERROR);
+    }
+}
+
+fn csv_options(args: &ArgMatches) -> CsvOptions {
+    let delimiter = match args.value_of("delimiter").unwrap() {
+        "\\t" | "tab" => b'\t',
+        d if d.len() == 1 => d.as_bytes()[0],
+        d => clap::Error::value_validation_auto(format!("--delimiter expects a single ASCII character, got `{}`", d)).exit(),
+    };
+    let quote_style = match args.value_of("quote").unwrap() {
+        "always" => QuoteStyle::Always,
+        "non-numeric" => QuoteStyle::NonNumeric,
+        "never" => QuoteStyle::Never,
+        _ => QuoteStyle::Necessary,
+    };
+    let terminator = match args.value_of("line-terminator").unwrap() {
+        "crlf" => Terminator::CRLF,
+        _ => Terminator::Any(b'\n'),
+    };
+    CsvOptions {
+        header: !args.is_present("no-header"),
+        delimiter,
+        quote_style,
+        terminator,
+        null: args.value_of("null").unwrap().to_string(),
+    }
+}
+
+fn sql_dialect(args: &ArgMatches) -> SqlDialect {
+    match args.value_of("dialect").unwrap() {
+        "postgresql" | "postgres" => SqlDialect::PostgreSql,
+        "sqlite" => SqlDialect::Sqlite,
+        _ => SqlDialect::MySql,
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(args: &ArgMatches, name: &str) -> T {
+    let value = args.value_of(name).unwrap();
+    match value.parse::<T>() {
+        Ok(n) => n,
+        Err(_) => clap::Error::value_validation_auto(format!("--{} expects a number, got `{}`", name, value)).exit(),
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use std::io;
     use std::fs::File;
-    use serde_json::{Value, Number};
+    use serde_json::Value;
     use serde_json::json;
     use data_faker_rust::configuration::FakerConfiguration;
-    use std::borrow::Borrow;
-    use data_faker_rust::configuration::definitions::{OutPutType, OutPutTypeValue};
-    use data_faker_rust::engine::FakerEngine;
+    use data_faker_rust::configuration::schema::Schema;
+    use data_faker_rust::configuration::ddl::starter_config;
+    use data_faker_rust::configuration::definitions::{OutPutType, OutPutTypeValue, SqlDialect};
+    use data_faker_rust::engine::{FakerEngine, SchemaEngine};
+    use data_faker_rust::engine::writer::CsvOptions;
+    use data_faker_rust::configuration::expression::{parse_rule, Argument};
+    use data_faker_rust::error::FakerError;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 在临时目录下为每次调用新建一个目录，写入名为 name 的文件并返回它的路径，
+    /// 并行运行的测试不会互相覆盖
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir()
+            .join(format!("data_faker_{}_{}", std::process::id(), COUNT.fetch_add(1, Ordering::SeqCst)));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// 把 content 写入临时的配置文件并加载
+    fn load_config(content: &str) -> Result<FakerConfiguration, FakerError> {
+        FakerConfiguration::read_from_file(temp_file("config.txt", content).to_str().unwrap())
+    }
 
     #[test]
     pub fn csv_test() {
@@ -48,21 +359,22 @@ mod test {
 
         // When writing records without Serde, the header record is written just
         // like any other record.
-        wtr.write_record(&["ci,ty", "region", "country", "population"]);
-        wtr.write_record(&["Southborough", "MA", "United States", "9686"]);
-        wtr.write_record(&["Northbridge", "MA", "United States", "14061"]);
-        wtr.flush();
+        wtr.write_record(["ci,ty", "region", "country", "population"]).unwrap();
+        wtr.write_record(["Southborough", "MA", "United States", "9686"]).unwrap();
+        wtr.write_record(["Northbridge", "MA", "United States", "14061"]).unwrap();
+        wtr.flush().unwrap();
     }
 
     #[test]
     pub fn json_test() {
-        let mut vec = Vec::<Value>::new();
-        vec.push(Value::String("123123".parse().unwrap()));
-        vec.push(Value::Bool(true));
-        vec.push(json!(123));
+        let vec = vec![
+            Value::String("123123".parse().unwrap()),
+            Value::Bool(true),
+            json!(123),
+        ];
 
         let v = Value::Array(vec);
-        println!("{}", v.to_string());
+        println!("{}", v);
 
         let john = json!({
             "name": "John Doe",
@@ -73,12 +385,12 @@ mod test {
             ]
         });
 
-        println!("{}", john.to_string());
+        println!("{}", john);
     }
 
     #[test]
     pub fn test_for_generate_json() {
-        let config = FakerConfiguration::read_from_file(String::from("test.txt").borrow()).unwrap();
+        let config = FakerConfiguration::read_from_file("test.txt").unwrap();
         let mut vec = vec![];
         for _ in 0..100 {
             let js = config.generate(OutPutType::Json).unwrap();
@@ -87,33 +399,728 @@ mod test {
             }
         }
         let out_json = Value::Array(vec);
-        println!("{}", out_json.to_string());
+        println!("{}", out_json);
     }
 
     #[test]
     pub fn test_for_generate_csv() {
-        let config = FakerConfiguration::read_from_file(String::from("test.txt").borrow()).unwrap();
+        let config = FakerConfiguration::read_from_file("test.txt").unwrap();
         let mut wtr = csv::Writer::from_writer(File::create("test.csv").unwrap());
-        // todo 写column名
+        wtr.write_record(config.header()).unwrap();
         for _ in 0..100 {
             let js = config.generate(OutPutType::Csv).unwrap();
             if let OutPutTypeValue::Csv(u) = js {
-                wtr.write_record(u);
+                wtr.write_record(u.iter().map(|v| v.as_deref().unwrap_or(""))).unwrap();
             }
         }
     }
 
     #[test]
     pub fn test_for_engine_json() {
-        //let config = FakerConfiguration::read_from_file(String::from("test.txt").borrow()).unwrap();
-        let eng = FakerEngine::new(&"test.txt".to_string(), 3).unwrap();
-        eng.manufacturing_to_file(OutPutType::Json, "test.json".to_string(), 100);
+        //let config = FakerConfiguration::read_from_file("test.txt").unwrap();
+        let eng = FakerEngine::new("test.txt", 3).unwrap();
+        eng.manufacturing_to_file(OutPutType::Json, "test.json".to_string(), 100).unwrap();
     }
 
     #[test]
     pub fn test_for_engine_csv() {
-        //let config = FakerConfiguration::read_from_file(String::from("test.txt").borrow()).unwrap();
-        let eng = FakerEngine::new(&"test.txt".to_string(), 3).unwrap();
-        eng.manufacturing_to_file(OutPutType::Csv, "tt1.csv".to_string(), 100);
+        //let config = FakerConfiguration::read_from_file("test.txt").unwrap();
+        let eng = FakerEngine::new("test.txt", 3).unwrap();
+        eng.manufacturing_to_file(OutPutType::Csv, "tt1.csv".to_string(), 100).unwrap();
+    }
+
+    #[test]
+    pub fn test_for_engine_row_count() {
+        for thread_count in 1..=8 {
+            let eng = FakerEngine::new("test.txt", thread_count).unwrap();
+            for &count in &[0, 1, 1023, 1024, 1025, 5000] {
+                let mut out = Vec::<u8>::new();
+                eng.manufacturing_to_writer(OutPutType::JsonLines, &mut out, count).unwrap();
+                let ids: Vec<u64> = String::from_utf8(out).unwrap().lines()
+                    .map(|l| serde_json::from_str::<Value>(l).unwrap()["id"].as_u64().unwrap())
+                    .collect();
+                assert_eq!(ids, (1..=count).collect::<Vec<_>>(), "thread_count = {}", thread_count);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_for_engine_unordered() {
+        let mut eng = FakerEngine::new("test.txt", 4).unwrap();
+        eng.set_seed(3);
+        let mut ordered = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::JsonLines, &mut ordered, 5000).unwrap();
+        eng.set_ordered(false);
+        let mut unordered = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::JsonLines, &mut unordered, 5000).unwrap();
+
+        let mut ordered: Vec<_> = String::from_utf8(ordered).unwrap().lines().map(String::from).collect();
+        let mut unordered: Vec<_> = String::from_utf8(unordered).unwrap().lines().map(String::from).collect();
+        ordered.sort();
+        unordered.sort();
+        assert_eq!(ordered, unordered);
+    }
+
+    #[test]
+    pub fn test_for_engine_json_lines() {
+        let mut eng = FakerEngine::new("test.txt", 3).unwrap();
+        eng.set_seed(7);
+        let mut array = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::Json, &mut array, 2500).unwrap();
+        let mut lines = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::JsonLines, &mut lines, 2500).unwrap();
+
+        let array: Vec<Value> = serde_json::from_slice(&array).unwrap();
+        let lines: Vec<Value> = String::from_utf8(lines).unwrap().lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(array.len(), 2500);
+        assert_eq!(array, lines);
+    }
+
+    #[test]
+    pub fn test_for_engine_csv_options() {
+        let mut eng = FakerEngine::new("test.txt", 2).unwrap();
+        eng.set_seed(1);
+        let mut out = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::Csv, &mut out, 2).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("id,name,file\n1,"));
+
+        eng.set_csv_options(CsvOptions {
+            header: false,
+            delimiter: b'\t',
+            quote_style: csv::QuoteStyle::Always,
+            terminator: csv::Terminator::CRLF,
+            null: "NULL".to_string(),
+        });
+        let mut out = Vec::<u8>::new();
+        eng.manufacturing_to_writer(OutPutType::Csv, &mut out, 2).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\"1\"\t\""));
+        assert_eq!(out.matches("\r\n").count(), 2);
+    }
+
+    #[test]
+    pub fn test_for_increase_concurrent() {
+        let config = FakerConfiguration::read_from_file("test.txt").unwrap();
+        let mut ids = std::sync::Mutex::new(Vec::new());
+        crossbeam::thread::scope(|a| {
+            for _ in 0..4 {
+                a.spawn(|_| {
+                    for _ in 0..1000 {
+                        if let OutPutTypeValue::Json(u) = config.generate(OutPutType::Json).unwrap() {
+                            ids.lock().unwrap().push(u["id"].as_i64().unwrap());
+                        }
+                    }
+                });
+            }
+        }).unwrap();
+        let ids = ids.get_mut().unwrap();
+        ids.sort();
+        assert_eq!(*ids, (1..=4000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn test_for_increase_overflow() {
+        let config = load_config("a||int||increase(9223372036854775806,1,saturate)\n\
+                                  b||int||increase(9223372036854775806,1,wrap)\n").unwrap();
+        let values: Vec<_> = (0..3).map(|row| match config.generate_row(OutPutType::Csv, row).unwrap() {
+            OutPutTypeValue::Csv(u) => u,
+            _ => unreachable!(),
+        }).collect();
+        assert_eq!(values[2], vec![Some(i64::MAX.to_string()), Some(i64::MIN.to_string())]);
+
+        let config = load_config("a||int||increase(-9223372036854775807,-1)\n").unwrap();
+        assert!(config.generate_row(OutPutType::Csv, 1).is_ok());
+        assert!(config.generate_row(OutPutType::Csv, 2).is_err());
+    }
+
+    #[test]
+    pub fn test_for_random_number() {
+        let config = load_config("a||int||randint(1,3)\nb||float||randfloat(0,1,1)\nc||string||randfloat(-1,1,2)\n").unwrap();
+        let mut ints = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            if let OutPutTypeValue::Json(u) = config.generate(OutPutType::Json).unwrap() {
+                ints.insert(u["a"].as_i64().unwrap());
+                let b = u["b"].as_f64().unwrap();
+                assert!((0.0..1.0).contains(&b));
+                assert_eq!(u["c"].as_str().unwrap().split('.').nth(1).unwrap().len(), 2);
+            }
+        }
+        assert_eq!(ints.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        for bad in &["a||int||randint(3,1)", "a||float||randfloat(1,1)", "a||float||randfloat(0.11,0.12,1)",
+            "a||int||randfloat(0,1)", "a||date||randint(0,1)"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_date() {
+        let config = load_config("a||date||date(2020-01-01,2020-01-02 12:00:00)\n\
+                                  b||date||date_increase(2020-01-31 23:00:00,1h)\n\
+                                  c||string||date_increase(2020-01-01,-1d)\n\
+                                  d||date||enum(2020-01-01,2021-06-01T08:00:00)\n\
+                                  e||date||now()\n").unwrap();
+        for row in 0..100 {
+            if let OutPutTypeValue::Json(u) = config.generate_row(OutPutType::Json, row).unwrap() {
+                let a = u["a"].as_str().unwrap();
+                assert!(("2020-01-01 00:00:00"..="2020-01-02 12:00:00").contains(&a));
+                if row == 2 {
+                    assert_eq!(u["b"], "2020-02-01 01:00:00");
+                    assert_eq!(u["c"], "2019-12-30 00:00:00");
+                }
+            }
+        }
+
+        for bad in &["a||date||date(2020-01-02,2020-01-01)", "a||date||date_increase(2020-01-01,1y)",
+            "a||int||now()", "a||date||enum(2020-13-01)", "a||date||date_increase(2020-01-01,9999999999999999d)",
+            "a||date||now()\nb||date||date_after(a,1d,99999999999999w)"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_date_format() {
+        let config = load_config("a||date||date_increase(2020-01-01 20:00:00,1d)\n\
+                                  b||date||date_increase(2020-01-01 20:00:00,1d)||tz=+08:00\n\
+                                  c||date||date_increase(2020-01-01 20:00:00,1d)||format=rfc3339;tz=-05:30\n\
+                                  d||date||date_increase(2020-01-01 20:00:00,1d)||format=unix_millis\n\
+                                  e||date||date_increase(2020-01-01 20:00:00,1d)||format=%d/%m/%Y %H时\n").unwrap();
+        if let OutPutTypeValue::Json(u) = config.generate_row(OutPutType::Json, 0).unwrap() {
+            assert_eq!(u["a"], "2020-01-01 20:00:00");
+            assert_eq!(u["b"], "2020-01-02 04:00:00");
+            assert_eq!(u["c"], "2020-01-01T14:30:00-05:30");
+            assert_eq!(u["d"], 1577908800000i64);
+            assert_eq!(u["e"], "01/01/2020 20时");
+        }
+        if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, 0).unwrap() {
+            assert_eq!(u[3].as_deref(), Some("1577908800000"));
+        }
+
+        for bad in &["a||date||now()||format=%Q", "a||date||now()||tz=Asia/Shanghai", "a||int||randint(0,1)||tz=+08:00"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_config_error() {
+        let path = temp_file("config.txt", "id||int||increase(1,1)\n# comment\nage||int||increase(1,x)\n");
+        let path = path.to_str().unwrap();
+        let err = FakerConfiguration::read_from_file(path).unwrap_err();
+        assert_eq!(err.to_string(),
+                   format!("{}:3: column `age`: invalid argument `x` for `increase`: invalid digit found in string", path));
+    }
+
+    #[test]
+    pub fn test_for_config_type_check() {
+        let path = temp_file("config.txt", "id||float||increase(1,1)\nname||int||enum(1,abc)\nok||string||enum(abc)\n");
+        let path = path.to_str().unwrap();
+        match FakerConfiguration::read_from_file(path).unwrap_err() {
+            FakerError::Multiple(errors) => {
+                assert_eq!(errors.len(), 2);
+                assert!(errors[0].to_string().starts_with(&format!("{}:1: column `id`", path)));
+                assert!(errors[1].to_string().starts_with(&format!("{}:2: column `name`", path)));
+            }
+            e => panic!("expected multiple errors, found {}", e),
+        }
+    }
+
+    #[test]
+    pub fn test_for_sql_types() {
+        let config = load_config("id||BIGINT UNSIGNED||increase(1,1)\n\
+                                  name||varchar(32)||enum(abc)\n\
+                                  price||decimal(10,2)||randfloat(1,2)\n\
+                                  active||boolean||enum(true)\n\
+                                  token||uuid||uuid()\n\
+                                  at||timestamp||date_increase(2020-01-01 20:00:00,1d)\n\
+                                  clock||time||date_increase(2020-01-01 20:00:00,1h)\n\
+                                  data||bytea||enum(ab)\n\
+                                  nothing||null||increase(1,1)\n").unwrap();
+        if let OutPutTypeValue::Json(u) = config.generate_row(OutPutType::Json, 1).unwrap() {
+            assert_eq!(u["id"], 2);
+            assert_eq!(u["name"], "abc");
+            assert_eq!(u["price"].as_str().unwrap().split('.').nth(1).unwrap().len(), 2);
+            assert_eq!(u["active"], true);
+            assert_eq!(u["token"].as_str().unwrap().chars().nth(14), Some('4'));
+            assert_eq!(u["at"], "2020-01-02T20:00:00+00:00");
+            assert_eq!(u["clock"], "21:00:00");
+            assert_eq!(u["data"], "6162");
+            assert_eq!(u["nothing"], Value::Null);
+        }
+        if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, 1).unwrap() {
+            assert_eq!(u[3].as_deref(), Some("true"));
+            assert_eq!(u[8], None);
+        }
+
+        for bad in &["a||decimal(50,2)||randint(0,1)", "a||decimal(4,2)||randint(0,1000)", "a||varchar(x)||enum(a)",
+            "a||boolean||enum(maybe)", "a||int||uuid()", "a||time||now()||format=unix",
+            "a||time||now()||format=%Y-%m-%d"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_nullable() {
+        let mut config = load_config("a||int||randint(0,100)\n\
+                                      b||int?||randint(0,100)||null_rate=0.3\n\
+                                      c||varchar?||enum(x)\n\
+                                      d||int||randint(0,100)||null_rate=1\n").unwrap();
+        config.seed = 7;
+        let mut plain = config.clone();
+        plain.items[1].null_rate = 0.0;
+        let mut nulls = 0;
+        for row in 0..1000 {
+            if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, row).unwrap() {
+                // 不为 null 的值与同一种子下不可为 null 的列相同
+                if let OutPutTypeValue::Csv(p) = plain.generate_row(OutPutType::Csv, row).unwrap() {
+                    assert!(u[1].is_none() || u[1] == p[1]);
+                }
+                nulls += u[1].is_none() as i32;
+                assert_eq!(u[3], None);
+            }
+        }
+        assert!(nulls > 200 && nulls < 400, "{}", nulls);
+        assert!(config.items[2].nullable && (config.items[2].null_rate - 0.1).abs() < 1e-9);
+        assert!(!config.items[0].nullable);
+
+        assert!(load_config("a||int||randint(0,1)||null_rate=2").is_err());
+    }
+
+    #[test]
+    pub fn test_for_weighted_enum() {
+        let values = temp_file("values.txt", "x,1\ny,0\nz,3\n");
+        let values = values.to_str().unwrap();
+        let cities = temp_file("cities.txt", "New York,1\nBoston,3\n");
+        let cities = cities.to_str().unwrap();
+        let config = load_config(&format!("status||string||enum(active:80,suspended:15,deleted:5)\n\
+                                          zero||string||enum(a:0,b:1)\n\
+                                          file||string||enum_file({},weighted)\n\
+                                          city||string||enum_file({})\n", values, cities)).unwrap();
+        let mut count = std::collections::HashMap::new();
+        for row in 0..10000 {
+            if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, row).unwrap() {
+                assert_eq!(u[1].as_deref(), Some("b"));
+                assert_ne!(u[2].as_deref(), Some("y"));
+                // 没有 weighted 时每一行原样作为一个值
+                assert!(matches!(u[3].as_deref(), Some("New York,1") | Some("Boston,3")), "{:?}", u[3]);
+                *count.entry(u[0].clone().unwrap()).or_insert(0) += 1;
+            }
+        }
+        assert!(count["active"] > 7700 && count["active"] < 8300, "{:?}", count);
+        assert!(count["deleted"] > 350 && count["deleted"] < 650, "{:?}", count);
+
+        for bad in &["a||string||enum(a:0,b:0)".to_string(), "a||string||enum(a:-1,b:2)".to_string(),
+            "a||time||enum(10:00,11:30)".to_string(), format!("a||string||enum_file({},weight)", values),
+            format!("a||string||enum_file({},weighted)", temp_file("bad.txt", "x,1\ny\n").to_str().unwrap())] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+
+        // 写在引号中的值，以及不是每个值都以 :数字 结尾时，: 保持原样
+        let config = load_config("clock||string||enum(\"10:00\",'11:30')\n\
+                                  at||date||enum(\"2020-01-01 08:00:00\",\"2021-01-01 09:00:00\")\n\
+                                  slot||string||enum(a:b,11:30)\n").unwrap();
+        let mut seen = std::collections::BTreeSet::new();
+        for row in 0..100 {
+            if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, row).unwrap() {
+                assert!(matches!(u[2].as_deref(), Some("a:b") | Some("11:30")), "{:?}", u[2]);
+                seen.insert(u[0].clone().unwrap());
+                seen.insert(u[1].clone().unwrap());
+            }
+        }
+        assert_eq!(seen.into_iter().collect::<Vec<_>>(),
+                   vec!["10:00", "11:30", "2020-01-01 08:00:00", "2021-01-01 09:00:00"]);
+    }
+
+    #[test]
+    pub fn test_for_rule_expression() {
+        let call = parse_rule(r#" Format ( "{} ({})", randint(1, 9) , name\,x, 'it\'s' ) "#).unwrap();
+        assert_eq!(call.name, "Format");
+        assert_eq!(call.args[0], Argument::Value { text: "{} ({})".to_string(), quoted: true });
+        match &call.args[1] {
+            Argument::Call(c) => {
+                assert_eq!(c.name, "randint");
+                assert_eq!(c.source, "randint(1, 9)");
+                assert_eq!(c.args.iter().map(|a| a.text()).collect::<Vec<_>>(), vec!["1", "9"]);
+            }
+            a => panic!("expected a nested rule, found {:?}", a),
+        }
+        assert_eq!(call.args[2].text(), "name,x");
+        assert_eq!(call.args[3].text(), "it's");
+        assert!(parse_rule("now()").unwrap().args.is_empty());
+        assert_eq!(parse_rule("concat(\"\")").unwrap().args[0], Argument::Value { text: String::new(), quoted: true });
+        // \n 这样的转义只在引号中处理
+        let call = parse_rule(r#"enum_file(C:\data\names.txt, "a\tb", a\tb)"#).unwrap();
+        assert_eq!(call.args.iter().map(|a| a.text()).collect::<Vec<_>>(), vec![r"C:\data\names.txt", "a\tb", r"a\tb"]);
+        assert_eq!(parse_rule("enum(,)").unwrap().args.len(), 2);
+        for bad in &["enum(a", "enum(\"a)", "enum(a) b", "enum(a(b)", "(a)", "enum(a\"b\")"] {
+            assert!(parse_rule(bad).is_err(), "{}", bad);
+        }
+
+        let config = load_config("name||string||ENUM(\"Smith, John\")\n\
+                                  time||string||enum(\"12:00\")\n\
+                                  spaced||string||enum( Jones )\n").unwrap();
+        if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, 0).unwrap() {
+            assert_eq!(u, vec![Some("Smith, John".to_string()), Some("12:00".to_string()), Some("Jones".to_string())]);
+        }
+    }
+
+    #[test]
+    pub fn test_for_regex() {
+        let config = load_config("order||string||regex(\"^ORD-[A-Z]{3}-\\d{6}$\")\n\
+                                  kind||string||regex(\"(?:foo|ba[rz])_[^0-9a-z]\")\n\
+                                  many||string||regex(\"x+\",2)\n\
+                                  star||string||regex(a*b?)\n").unwrap();
+        for row in 0..200 {
+            if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, row).unwrap() {
+                let order: Vec<char> = u[0].as_ref().unwrap().chars().collect();
+                assert_eq!(order.len(), 14);
+                assert!(order[..4] == ['O', 'R', 'D', '-'] && order[7] == '-');
+                assert!(order[4..7].iter().all(|c| c.is_ascii_uppercase()));
+                assert!(order[8..].iter().all(|c| c.is_ascii_digit()));
+
+                let kind = u[1].as_ref().unwrap();
+                assert!(["foo_", "bar_", "baz_"].contains(&&kind[..4]), "{}", kind);
+                let last = kind.chars().nth(4).unwrap();
+                assert!(kind.len() == 5 && !last.is_ascii_digit() && !last.is_ascii_lowercase(), "{}", kind);
+
+                let many = u[2].as_ref().unwrap();
+                assert!((1..=3).contains(&many.len()) && many.chars().all(|c| c == 'x'));
+                assert!(u[3].as_ref().unwrap().len() <= 9);
+            }
+        }
+
+        for bad in &["a||string||regex(\"[a-\")", "a||string||regex(\"(ab\")", "a||string||regex(\"*a\")",
+            "a||string||regex(\"a{3,1}\")", "a||string||regex(\"(?=a)\")", "a||string||regex(\"\\p{L}\")", "a||int||regex(a)",
+            "a||string||regex(\"a{0,4294967295}\")", "a||string||regex(\"a{0,99999999999}\")",
+            "a||string||regex(\"a{0,1000000000}\")", "a||string||regex(\"a{1001,}\")", "a||string||regex(\"a*\",4294967295)"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+
+        // 次数的上限本身是允许的
+        let config = load_config("a||string||regex(\"a{1000}\")\nb||string||regex(\"b*\",1000)\n").unwrap();
+        if let OutPutTypeValue::Csv(u) = config.generate_row(OutPutType::Csv, 0).unwrap() {
+            assert_eq!(u[0].as_ref().unwrap().len(), 1000);
+            assert!(u[1].as_ref().unwrap().len() <= 1000);
+        }
+    }
+
+    #[test]
+    pub fn test_for_format() {
+        let config = load_config("id||int||increase(1,1)\n\
+                                  first||string||enum(Ann)\n\
+                                  born||date||date_increase(2020-01-01,1d)||format=%Y%m%d\n\
+                                  email||string||format(\"{}.{}{{{1}}}@example.com\",first,regex(\"[0-9]{2}\"))\n\
+                                  code||string||format(\"{}-{}-{}\",\"X\",id,format(\"{}\",born))\n\
+                                  pipe||string||format('{}||{}',first,\"||\")||seed=1\n\
+                                  empty||string||concat(first,\"\",'',first)\n").unwrap();
+        if let OutPutTypeValue::Json(u) = config.generate_row(OutPutType::Json, 1).unwrap() {
+            let email = u["email"].as_str().unwrap();
+            assert!(email.starts_with("Ann.") && email.ends_with("}@example.com"), "{}", email);
+            assert_eq!(&email[4..6], &email[7..9]);
+            assert_eq!(u["code"], "X-2-20200102");
+            assert_eq!(u["pipe"], "Ann||||");
+            assert_eq!(u["empty"], "AnnAnn");
+        }
+
+        for bad in &["a||string||format(\"{}\",b)\nc||string||enum(x)", "a||string||format(\"{} {}\",\"x\")",
+            "a||int||format(\"x\")", "a||string||format(\"{\")", "a||string||format(\"{}\",nope(1))"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_column_reference() {
+        let config = load_config("full_name||string||concat(first_name,\" \",last_name)\n\
+                                  first_name||string||enum(Ann,Bob)\n\
+                                  last_name||string||enum(Lee)\n\
+                                  start_date||date?||date(2020-01-01,2020-12-31)||format=%Y-%m-%d %H:%M:%S\n\
+                                  end_date||date||date_after(start_date,1d,30d)\n\
+                                  score||int||randint(1,100)\n\
+                                  score_f||float||ref(score)\n").unwrap();
+        assert_eq!(config.header()[0], "full_name");
+        for row in 0..100 {
+            if let OutPutTypeValue::Json(u) = config.generate_row(OutPutType::Json, row).unwrap() {
+                assert_eq!(u["full_name"], format!("{} Lee", u["first_name"].as_str().unwrap()));
+                assert_eq!(u["score"].as_i64().unwrap() as f64, u["score_f"].as_f64().unwrap());
+                match u["start_date"].as_str() {
+                    None => assert!(u["end_date"].is_null()),
+                    Some(start) => {
+                        // 同一格式的时间按字符串比较即可
+                        let end = u["end_date"].as_str().unwrap();
+                        assert!(end > start, "{} {}", start, end);
+                        assert!(end < "2021-01-31", "{}", end);
+                    }
+                }
+            }
+        }
+
+        let err = load_config("a||string||concat(b)\nb||string||format(\"{}\",c)\nc||string||ref(a)\n").unwrap_err().to_string();
+        assert!(err.ends_with("column `a`: columns refer to each other: `a` -> `b` -> `c` -> `a`"), "{}", err);
+        // 引用的列类型不对时在读取配置时报错
+        let err = load_config("a||int||randint(1,2)\nb||date||date_after(a,1d,2d)\n").unwrap_err().to_string();
+        assert!(err.ends_with("column `b`: invalid argument `a` for `date_after`: expected a date column, found Integer"), "{}", err);
+        // format 和 concat 中嵌套的规则也会检查
+        let err = load_config("a||int||randint(1,2)\nb||string||format(\"{}\",date_after(a,1d,2d))\n").unwrap_err().to_string();
+        assert!(err.ends_with("column `b`: invalid argument `a` for `date_after`: expected a date column, found Integer"), "{}", err);
+        assert!(load_config("a||int||randint(1,2)\nc||date||now()\nb||string||concat(c,date_after(a,1d,2d))").is_err());
+        assert!(load_config("a||int||randint(1,2)\nc||date||now()\nb||string||concat(a,date_after(c,1d,2d))").is_ok());
+        for bad in &["a||string||ref(a)", "a||string||ref(x)", "a||time||now()\nb||date||date_after(a,1d,2d)",
+            "a||date||now()\nb||int||ref(a)", "a||boolean||enum(true)\nb||date||ref(a)", "a||float||randfloat(0,1)\nb||int||ref(a)"] {
+            assert!(load_config(bad).is_err(), "{}", bad);
+        }
+        assert!(load_config("a||date||now()\nb||timestamp||ref(a)\nc||decimal(5,2)||randint(1,2)\nd||float||ref(c)\n\
+                             e||boolean?||enum(true)\nf||string||ref(e)\ng||null||enum(x)\nh||date||date_after(g,1d,2d)").is_ok());
+    }
+
+    #[test]
+    pub fn test_for_schema() {
+        let path = temp_file("schema.txt", "# orders 写在 users 前面\n\
+                                            [orders] count=200\n\
+                                            id||int||increase(1,1)\n\
+                                            user_id||int||fk(users.id)\n\
+                                            [users] count=20\n\
+                                            id||int||increase(1000,7)\n\
+                                            name||string||enum(Ann,Bob)\n");
+        let mut engine = SchemaEngine::new(path.to_str().unwrap(), 3).unwrap();
+        engine.set_seed(5);
+        assert_eq!(engine.tables(), vec!["users", "orders"]);
+
+        let out = path.with_file_name("out");
+        engine.manufacturing_to_dir(OutPutType::JsonLines, out.to_str().unwrap()).unwrap();
+        let read = |table: &str| -> Vec<Value> {
+            std::fs::read_to_string(out.join(format!("{}.jsonl", table))).unwrap()
+                .lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+        };
+        let users = read("users");
+        let ids: Vec<&Value> = users.iter().map(|u| &u["id"]).collect();
+        assert_eq!(users.len(), 20);
+        let orders = read("orders");
+        assert_eq!(orders.len(), 200);
+        assert!(orders.iter().all(|o| ids.contains(&&o["user_id"])));
+
+        for bad in &["[a]\nx||int||fk(b.x)\n[b]\nx||int||fk(a.x)", "[a]\nx||int||fk(c.x)",
+            "[a]\nx||int||increase(1,1)\ny||int||fk(a.x)", "[a]\nx||int||increase(1,1)\n[b]\ny||int||fk(a.z)",
+            "x||int||increase(1,1)", "[a] size=3\nx||int||increase(1,1)", "[a]\n[a]"] {
+            let path = temp_file("schema.txt", bad);
+            assert!(SchemaEngine::new(path.to_str().unwrap(), 1).is_err(), "{}", bad);
+        }
+
+        // 只有一张表的配置在读取时就拒绝外键，不会等到开始输出之后才出错
+        let err = load_config("id||int||increase(1,1)\nuser_id||int||fk(users.id)\n").unwrap_err().to_string();
+        assert!(err.ends_with(":2: column `user_id`: unknown table `users`"), "{}", err);
+    }
+
+    #[test]
+    pub fn test_for_sql_output() {
+        let path = temp_file("data_faker_users.txt", "id||int||increase(1,1)\n\
+                                                      name||string||enum(\"O'Brien\\\\\")\n\
+                                                      ok||boolean||enum(true)\n\
+                                                      data||bytes||enum(ab)\n\
+                                                      price||decimal(5,2)||enum(1.5)\n\
+                                                      gone||int?||randint(1,2)||null_rate=1\n");
+        let generate = |dialect: SqlDialect, table: Option<&str>| {
+            let mut eng = FakerEngine::new(path.to_str().unwrap(), 2).unwrap();
+            if let Some(t) = table {
+                eng.set_table_name(t);
+            }
+            let mut out = Vec::<u8>::new();
+            eng.manufacturing_to_writer(OutPutType::Sql { dialect, batch_size: 2 }, &mut out, 3).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(generate(SqlDialect::MySql, None),
+                   "INSERT INTO `data_faker_users` (`id`, `name`, `ok`, `data`, `price`, `gone`) VALUES\n\
+                    (1, 'O''Brien\\\\', TRUE, X'6162', 1.50, NULL),\n\
+                    (2, 'O''Brien\\\\', TRUE, X'6162', 1.50, NULL);\n\
+                    INSERT INTO `data_faker_users` (`id`, `name`, `ok`, `data`, `price`, `gone`) VALUES\n\
+                    (3, 'O''Brien\\\\', TRUE, X'6162', 1.50, NULL);\n");
+        let pg = generate(SqlDialect::PostgreSql, Some("my\"users"));
+        assert!(pg.starts_with("INSERT INTO \"my\"\"users\" (\"id\", "), "{}", pg);
+        assert!(pg.contains("(1, 'O''Brien\\', TRUE, '\\x6162', 1.50, NULL)"), "{}", pg);
+        let sqlite = generate(SqlDialect::Sqlite, None);
+        assert!(sqlite.contains("(3, 'O''Brien\\', 1, X'6162', 1.50, NULL);\n"), "{}", sqlite);
+        assert_eq!(sqlite.matches("INSERT INTO").count(), 2);
+    }
+
+    #[test]
+    pub fn test_for_create_table() {
+        let config = load_config("id||int||increase(1,1)\n\
+                                  name||varchar?||enum(a)\n\
+                                  price||decimal(5,2)||enum(1.5)\n\
+                                  born||date||date(2020-01-01,2020-12-31)||format=unix\n\
+                                  token||uuid||uuid()\n").unwrap();
+        assert_eq!(config.create_table("users", SqlDialect::MySql),
+                   "CREATE TABLE `users` (\n  `id` BIGINT NOT NULL,\n  `name` TEXT,\n  `price` DECIMAL(5,2) NOT NULL,\n  \
+                    `born` BIGINT NOT NULL,\n  `token` CHAR(36) NOT NULL\n);\n");
+        let pg = config.create_table("users", SqlDialect::PostgreSql);
+        assert!(pg.contains("\"price\" NUMERIC(5,2) NOT NULL") && pg.contains("\"token\" UUID NOT NULL"), "{}", pg);
+        let sqlite = config.create_table("users", SqlDialect::Sqlite);
+        assert!(sqlite.contains("\"id\" INTEGER NOT NULL") && sqlite.contains("\"token\" TEXT NOT NULL"), "{}", sqlite);
+
+        let path = temp_file("schema.txt", "[orders]\nuser_id||int||fk(users.id)\n[users]\nid||int||increase(1,1)\n");
+        let ddl = Schema::read_from_file(path.to_str().unwrap()).unwrap().create_tables(SqlDialect::Sqlite);
+        assert!(ddl.find("\"users\"").unwrap() < ddl.find("\"orders\"").unwrap(), "{}", ddl);
+    }
+
+    #[test]
+    pub fn test_for_import_ddl() {
+        let config = starter_config("-- 用户表\n\
+            CREATE TABLE IF NOT EXISTS `shop`.`users` (\n\
+              `id` int(11) unsigned NOT NULL AUTO_INCREMENT,\n\
+              `email` varchar(255) NOT NULL,\n\
+              `status` enum('active','it''s') NOT NULL DEFAULT 'active',\n\
+              `price` decimal(4,2),\n\
+              `created_at` datetime NOT NULL COMMENT 'a, b',\n\
+              PRIMARY KEY (`id`)\n\
+            ) ENGINE=InnoDB;").unwrap();
+        assert_eq!(config, "id||int unsigned||increase(1,1)\n\
+                            email||varchar(255)||regex(\"[a-z]{3,8}\\.[a-z]{3,8}@example\\.(com|org|net)\")\n\
+                            status||enum||enum(active,\"it's\")\n\
+                            price||decimal(4,2)?||randfloat(0,99)\n\
+                            created_at||datetime||date(2020-01-01,2024-12-31)\n");
+        let users = load_config(&config).unwrap();
+        if let OutPutTypeValue::Json(u) = users.generate_row(OutPutType::Json, 0).unwrap() {
+            assert_eq!(u["id"], 1);
+            assert!(u["email"].as_str().unwrap().contains("@example."));
+        }
+
+        // 以 :数字 结尾的值加上引号，不会被当成权重
+        let slots = starter_config("create table slots (at enum('10:00','11:30') not null);").unwrap();
+        assert_eq!(slots, "at||enum||enum(\"10:00\",\"11:30\")\n");
+        assert!(load_config(&slots).is_ok());
+
+        let schema = starter_config("create table orders (order_no bigserial primary key, user_id int references users(id), \
+                                     note text); create table users (id serial, name varchar(20));").unwrap();
+        assert!(schema.starts_with("[orders]\norder_no||bigserial||increase(1,1)\nuser_id||int?||fk(users.id)\n"), "{}", schema);
+        let path = temp_file("schema.txt", &schema);
+        assert!(Schema::read_from_file(path.to_str().unwrap()).is_ok(), "{}", schema);
+
+        // 引用自己所在的表的外键按类型生成
+        let emp = starter_config("create table emp (id int primary key, manager_id int references emp(id));").unwrap();
+        assert!(!emp.contains("fk("), "{}", emp);
+        assert!(load_config(&emp).is_ok(), "{}", emp);
+        let schema = starter_config("create table dept (id int primary key); create table emp (id int primary key, \
+                                     dept_id int references dept(id), manager_id int references emp(id));").unwrap();
+        assert!(schema.contains("dept_id||int?||fk(dept.id)\n") && !schema.contains("fk(emp.id)"), "{}", schema);
+        let path = temp_file("schema.txt", &schema);
+        assert!(Schema::read_from_file(path.to_str().unwrap()).is_ok(), "{}", schema);
+
+        for bad in &["select 1", "create table t (a int", "create table t (a)", "create table t (a int); create table t (b int)"] {
+            assert!(starter_config(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    pub fn test_for_parquet_output() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::{Field, RowAccessor};
+
+        let path = temp_file("config.txt", "id||int||increase(1,1)\n\
+                                            score||double||randfloat(0,1)\n\
+                                            name||varchar?||enum(Ann)||null_rate=0.5\n\
+                                            at||timestamp||date_increase(2020-01-01,1h)\n\
+                                            price||decimal(6,2)||enum(12.5)\n\
+                                            ok||boolean||enum(true)\n\
+                                            token||uuid||uuid()\n");
+        let out = path.with_extension("parquet");
+        let eng = FakerEngine::new(path.to_str().unwrap(), 3).unwrap();
+        eng.manufacturing_to_file(OutPutType::Parquet, out.to_str().unwrap().to_string(), 70000).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&out).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 70000);
+        assert_eq!(metadata.num_row_groups(), 2);
+        let schema = metadata.file_metadata().schema_descr();
+        assert_eq!(schema.column(0).physical_type(), parquet::basic::Type::INT64);
+        assert!(!schema.column(0).self_type().is_optional() && schema.column(2).self_type().is_optional());
+
+        let mut nulls = 0;
+        for (i, row) in reader.get_row_iter(None).unwrap().enumerate() {
+            let row = row.unwrap();
+            assert_eq!(row.get_long(0).unwrap(), i as i64 + 1);
+            assert!((0.0..1.0).contains(&row.get_double(1).unwrap()));
+            match row.get_string(2) {
+                Ok(name) => assert_eq!(name, "Ann"),
+                Err(_) => nulls += 1,
+            }
+            assert_eq!(row.get_timestamp_millis(3).unwrap(), 1577836800000 + i as i64 * 3600000);
+            assert_eq!(row.get_decimal(4).unwrap().data(), 1250i64.to_be_bytes());
+            assert!(row.get_bool(5).unwrap());
+            assert!(matches!(row.get_column_iter().nth(6).unwrap().1, Field::Bytes(b) if b.len() == 16));
+        }
+        assert!(nulls > 30000 && nulls < 40000, "{}", nulls);
+
+        let path = temp_file("config.txt", "a||int?||randint(1,2)||null_rate=1\nb||int||ref(a)\n");
+        let eng = FakerEngine::new(path.to_str().unwrap(), 1).unwrap();
+        assert!(eng.manufacturing_to_writer(OutPutType::Parquet, Vec::new(), 10).is_err());
+    }
+
+    #[test]
+    pub fn test_for_avro_output() {
+        use apache_avro::types::Value as AvroValue;
+
+        let path = temp_file("config.txt", "id||int||increase(1,1)\n\
+                                            name||varchar?||enum(Ann)||null_rate=0.5\n\
+                                            at||timestamp||date_increase(2020-01-01,1h)\n\
+                                            price||decimal(6,2)||enum(12.5)\n\
+                                            token||uuid||uuid()\n\
+                                            nothing||null||enum(x)\n");
+        let generate = |seed: u64| {
+            let mut eng = FakerEngine::new(path.to_str().unwrap(), 3).unwrap();
+            eng.set_seed(seed);
+            eng.set_table_name("user-events");
+            let mut out = Vec::<u8>::new();
+            eng.manufacturing_to_writer(OutPutType::Avro, &mut out, 5000).unwrap();
+            out
+        };
+        let out = generate(9);
+        // 文件头中元数据的顺序不固定，比较读出的行和结尾的同步标记
+        let again = generate(9);
+        let rows = |b: &[u8]| apache_avro::Reader::new(b).unwrap().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(rows(&out), rows(&again));
+        assert_eq!(out[out.len() - 16..], again[again.len() - 16..]);
+        let other = generate(10);
+        assert_ne!(out[out.len() - 16..], other[other.len() - 16..]);
+
+        let reader = apache_avro::Reader::new(&out[..]).unwrap();
+        let schema: Value = serde_json::to_value(reader.writer_schema()).unwrap();
+        assert_eq!(schema["name"], "user_events");
+        assert_eq!(schema["fields"][0]["type"], "long");
+        assert_eq!(schema["fields"][1]["type"], json!(["null", "string"]));
+        assert_eq!(schema["fields"][2]["type"]["logicalType"], "timestamp-millis");
+        assert_eq!(schema["fields"][5]["type"], "null");
+        let mut nulls = 0;
+        for (i, row) in reader.enumerate() {
+            let fields = match row.unwrap() {
+                AvroValue::Record(f) => f,
+                v => panic!("expected a record, found {:?}", v),
+            };
+            assert_eq!(fields[0].1, AvroValue::Long(i as i64 + 1));
+            match &fields[1].1 {
+                AvroValue::Union(0, v) => {
+                    assert_eq!(**v, AvroValue::Null);
+                    nulls += 1;
+                }
+                v => assert_eq!(*v, AvroValue::Union(1, Box::new(AvroValue::String("Ann".to_string())))),
+            }
+            assert_eq!(fields[2].1, AvroValue::TimestampMillis(1577836800000 + i as i64 * 3600000));
+            assert!(matches!(&fields[3].1, AvroValue::Decimal(_)));
+            assert!(matches!(&fields[4].1, AvroValue::Uuid(u) if u.get_version_num() == 4));
+            assert_eq!(fields[5].1, AvroValue::Null);
+        }
+        assert!(nulls > 2000 && nulls < 3000, "{}", nulls);
+    }
+
+    #[test]
+    pub fn test_for_engine_seed() {
+        let generate = |thread_count: i32| {
+            let mut eng = FakerEngine::new("test.txt", thread_count).unwrap();
+            eng.set_seed(42);
+            let mut out = Vec::<u8>::new();
+            eng.manufacturing_to_writer(OutPutType::Csv, &mut out, 100).unwrap();
+            out
+        };
+        assert_eq!(generate(3), generate(3));
+        assert_eq!(generate(3), generate(5));
     }
 }
@@ -1,82 +1,118 @@
-use std::sync::{Mutex, Arc};
-use crate::configuration::definitions::{GenerateRule, VariableType, VariableTypeValue, ConstructRule, remove_parentheses};
-use std::error::Error;
-use std::fmt::Debug;
-use serde::export::Formatter;
-use core::fmt;
-use std::ops::Deref;
-use std::borrow::Borrow;
-use rand::prelude::ThreadRng;
+use crate::configuration::definitions::{GenerateRule, VariableType, VariableTypeValue, ConstructRule, remove_parentheses, GenerateContext, parse_decimal,
+                                               construct_from_str, ColumnValue,
+                                               uuid_v4, uuid_to_string};
+use crate::configuration::expression::{parse_rule, Argument};
+use crate::error::FakerError;
 use rand::Rng;
-use std::num::ParseIntError;
+use rand::distributions::Uniform;
+use rand::distributions::weighted::alias_method::WeightedIndex;
 use std::fs::read_to_string;
+use std::convert::TryFrom;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+mod regex;
+
+pub use self::regex::RegexRule;
 
 /// increase(start,step)
-/// increase(INTEGER,INTEGER)
-#[derive(Debug)]
+/// increase(start,step,overflow)
+/// increase(INTEGER,INTEGER,error|wrap|saturate)
+/// 第 n 行（从 0 开始）的值为 start + n * step。
+/// 行号由 FakerConfiguration 的原子计数器或引擎按块分配，每一行只会被分配一次，
+/// 所以多线程下也不会重复或跳号，生成时不需要加锁
+/// overflow 决定超出 i64 范围时的行为，默认 error
+#[derive(Debug, Clone)]
 pub struct IncreaseRule {
     pub(crate) start: i64,
     pub(crate) step: i64,
-    pub(crate) now: Arc<Mutex<i64>>,
+    pub(crate) overflow: Overflow,
+}
+
+/// 数列超出 i64 范围时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// 返回错误
+    Error,
+    /// 按补码回绕
+    Wrap,
+    /// 停在 i64::MAX 或 i64::MIN
+    Saturate,
 }
 
 impl ConstructRule for IncreaseRule {
-    fn construct(s: String) -> Result<Box<Self>, ()> {
-        let args = remove_parentheses(s.borrow())?;
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
 
         //check number of parameter
-        if args.len() != 2 {
-            return Err(());
+        if args.len() != 2 && args.len() != 3 {
+            return Err(FakerError::ArgumentCount {
+                rule: "increase".to_string(),
+                expected: "2 or 3".to_string(),
+                found: args.len(),
+            });
         }
 
-        let start: i64 = match args[0].parse::<i64>() {
-            Ok(o) => { o }
-            Err(_) => { return Err(()); }
-        };
+        let start: i64 = args[0].parse::<i64>()
+            .map_err(|e| FakerError::invalid_argument("increase", &args[0], e))?;
+
+        let step: i64 = args[1].parse::<i64>()
+            .map_err(|e| FakerError::invalid_argument("increase", &args[1], e))?;
 
-        let step: i64 = match args[1].parse::<i64>() {
-            Ok(o) => { o }
-            Err(_) => { return Err(()); }
+        let overflow = match args.get(2).map(|s| s.trim()) {
+            None | Some("error") => Overflow::Error,
+            Some("wrap") => Overflow::Wrap,
+            Some("saturate") => Overflow::Saturate,
+            Some(o) => {
+                return Err(FakerError::invalid_argument("increase", o, "expected error, wrap or saturate"));
+            }
         };
 
-        return Ok(Box::new(IncreaseRule {
+        Ok(Box::new(IncreaseRule {
             start,
             step,
-            now: Arc::new(Mutex::new(start)),
-        }));
+            overflow,
+        }))
     }
 }
 
 impl GenerateRule for IncreaseRule {
-    fn generate_into(&self, into_type: VariableType) -> Result<VariableTypeValue, ()> {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
         match into_type {
             VariableType::Integer => {
-                let s = self.now.lock().unwrap().deref().clone();
-                *self.now.lock().unwrap() += self.step;
-                return Ok(VariableTypeValue::Integer(s));
-            }
-            VariableType::Float => {
-                return Err(());
+                let s = self.nth(ctx.row)?;
+                Ok(VariableTypeValue::Integer(s))
             }
             VariableType::String => {
-                let s = self.now.lock().unwrap().deref().clone();
-                *self.now.lock().unwrap() += self.step;
-                return Ok(VariableTypeValue::String(format!("{}", s)));
+                let s = self.nth(ctx.row)?;
+                Ok(VariableTypeValue::String(format!("{}", s)))
             }
-            VariableType::Date => {
-                return Err(());
-            }
-        };
+            _ => Err(FakerError::unsupported_type("increase", into_type)),
+        }
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::Integer | VariableType::String => Ok(()),
+            _ => Err(FakerError::unsupported_type("increase", into_type.clone())),
+        }
     }
 }
 
-impl Clone for IncreaseRule {
-    fn clone(&self) -> Self {
-        return Self {
-            start: self.start,
-            step: self.step,
-            now: Arc::from(Mutex::new(self.now.lock().unwrap().deref().clone())),
-        };
+impl IncreaseRule {
+    /// 第 n 项，用 i128 计算避免中间结果溢出
+    fn nth(&self, n: u64) -> Result<i64, FakerError> {
+        let v = self.start as i128 + n as i128 * self.step as i128;
+        if v >= i64::MIN as i128 && v <= i64::MAX as i128 {
+            return Ok(v as i64);
+        }
+        match self.overflow {
+            Overflow::Error => Err(FakerError::Overflow {
+                rule: "increase".to_string(),
+                row: n,
+            }),
+            Overflow::Wrap => Ok(v as i64),
+            Overflow::Saturate => Ok(if v > 0 { i64::MAX } else { i64::MIN }),
+        }
     }
 }
 
@@ -87,149 +123,1017 @@ impl Clone for IncreaseRule {
 /// var1||int||enum(1,2,3,4,5)
 /// var2||float||enum(1.0,2.0,3.0,4.0,5.0)
 /// var3||string||enum(abc,def,dds,ffq,eer,wwt)
+/// 每个值后面都写上 :权重 时按权重抽取，例如
+/// var4||string||enum(active:80,suspended:15,deleted:5)
+/// 写在引号中的值不带权重，其中的 : 保持原样，例如 enum("10:00","11:30")
 #[derive(Debug, Clone)]
 pub struct EnumRule {
-    enumeration: Vec<String>,
+    enumeration: Enumeration,
 }
 
 impl ConstructRule for EnumRule {
-    fn construct(s: String) -> Result<Box<Self>, ()> {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
         //enum(var1,var2,...)
         //enum(STRING|INTEGER|FLOAT|DATE,...)
-        let args = remove_parentheses(s.borrow())?;
+        //enum(var1:weight1,var2:weight2,...)
+        let args = parse_rule(&s)?.args;
 
         //check number of parameter
-        if args.len() == 0 {
-            return Err(());
+        if args.is_empty() {
+            return Err(FakerError::ArgumentCount {
+                rule: "enum".to_string(),
+                expected: "at least 1".to_string(),
+                found: 0,
+            });
+        }
+
+        // 每个值都以引号外的 :数字 结尾时才按权重抽取
+        let weighted: Option<Vec<(&str, f64)>> = args.iter()
+            .map(|a| match a {
+                Argument::Value { text, quoted: false } => split_weight(text),
+                _ => None,
+            })
+            .collect();
+        let enumeration = match weighted {
+            Some(pairs) => {
+                let (values, weights) = pairs.into_iter().map(|(v, w)| (v.to_string(), w)).unzip();
+                Enumeration::new("enum", &s, values, Some(weights))?
+            }
+            None => Enumeration::new("enum", &s, args.iter().map(|a| a.text().to_string()).collect(), None)?,
+        };
+
+        Ok(Box::new(Self { enumeration }))
+    }
+}
+
+impl GenerateRule for EnumRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        parse_enumeration("enum", self.enumeration.sample(ctx), into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_enumeration("enum", &self.enumeration.values, into_type)
+    }
+}
+
+/// 枚举的值和可选的权重
+#[derive(Debug, Clone)]
+struct Enumeration {
+    values: Vec<String>,
+    /// 带权重时的别名表，抽样的时间与值的个数无关
+    weights: Option<WeightedIndex<f64>>,
+}
+
+impl Enumeration {
+    /// weights 为 None 时所有值等概率，arg 用于报错
+    fn new(rule: &str, arg: &str, values: Vec<String>, weights: Option<Vec<f64>>) -> Result<Self, FakerError> {
+        let weights = weights
+            .map(|w| WeightedIndex::new(w).map_err(|e| FakerError::invalid_argument(rule, arg, e)))
+            .transpose()?;
+        Ok(Enumeration { values, weights })
+    }
+
+    fn sample(&self, ctx: &mut GenerateContext) -> &str {
+        let idx = match &self.weights {
+            Some(weights) => ctx.rng.sample(weights),
+            None => ctx.rng.gen_range(0, self.values.len()),
+        };
+        &self.values[idx]
+    }
+}
+
+/// 把 active:80 拆成值和权重，最后一个 : 之后不是数字时返回 None
+pub(crate) fn split_weight(text: &str) -> Option<(&str, f64)> {
+    let idx = text.rfind(':')?;
+    let weight = text[idx + 1..].trim().parse::<f64>().ok()?;
+    Some((&text[..idx], weight))
+}
+
+/// 把枚举出的字符串转换成对应的数据类型
+fn parse_enumeration(rule: &str, en: &str, into_type: VariableType) -> Result<VariableTypeValue, FakerError> {
+    VariableTypeValue::parse(en, &into_type)
+        .map_err(|e| FakerError::invalid_value(rule, en, into_type, e))
+}
+
+/// 检查每一个枚举值都能转换成对应的数据类型
+fn check_enumeration(rule: &str, enumeration: &[String], into_type: &VariableType) -> Result<(), FakerError> {
+    for en in enumeration {
+        parse_enumeration(rule, en, into_type.clone())?;
+    }
+    Ok(())
+}
+
+// ------------ end of the EnumRule ---------
+
+/// enum_file(path)
+/// enum_file(path,weighted)
+/// 文件中的数据换行隔开，忽略空行，每一行原样作为一个值
+/// 第二个参数为 weighted 时每一行写成 值,权重，按权重抽取
+#[derive(Debug, Clone)]
+pub struct EnumFileRule {
+    enumeration: Enumeration,
+}
+
+impl GenerateRule for EnumFileRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        parse_enumeration("enum_file", self.enumeration.sample(ctx), into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_enumeration("enum_file", &self.enumeration.values, into_type)
+    }
+}
+
+impl ConstructRule for EnumFileRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.is_empty() || args.len() > 2 {
+            return Err(FakerError::ArgumentCount {
+                rule: "enum_file".to_string(),
+                expected: "1 or 2".to_string(),
+                found: args.len(),
+            });
         }
+        let weighted = match args.get(1).map(|a| a.trim()) {
+            None => false,
+            Some(a) if a.eq_ignore_ascii_case("weighted") => true,
+            Some(a) => return Err(FakerError::invalid_argument("enum_file", a, "expected `weighted`")),
+        };
 
-        let mut enumeration = vec![];
+        let path = &args[0];
+        let file = read_to_string(path)
+            .map_err(|e| FakerError::invalid_argument("enum_file", path, e))?;
+        let v: Vec<_> = file.lines().filter(|ss| {
+            !ss.is_empty()
+        }).collect();
 
-        for i in args {
-            enumeration.push(String::from(i));
+        if v.is_empty() {
+            return Err(FakerError::invalid_argument("enum_file", path, "file has no values"));
         }
 
-        Ok(Box::new(Self {
-            enumeration,
+        let enumeration = if weighted {
+            let mut values = Vec::with_capacity(v.len());
+            let mut weights = Vec::with_capacity(v.len());
+            for ss in v {
+                let idx = ss.rfind(',')
+                    .ok_or_else(|| FakerError::invalid_argument("enum_file", ss, "expected a line like value,weight"))?;
+                let weight = ss[idx + 1..].trim().parse::<f64>()
+                    .map_err(|e| FakerError::invalid_argument("enum_file", ss, e))?;
+                values.push(ss[..idx].to_string());
+                weights.push(weight);
+            }
+            Enumeration::new("enum_file", path, values, Some(weights))?
+        } else {
+            Enumeration::new("enum_file", path, v.iter().map(|ss| ss.to_string()).collect(), None)?
+        };
+
+        Ok(Box::new(EnumFileRule { enumeration }))
+    }
+}
+
+// ------------ end of the EnumFileRule ---------
+
+/// randint(min,max)
+/// randint(INTEGER,INTEGER)
+/// 在 [min, max] 中均匀随机取一个整数，包含两端
+#[derive(Debug, Clone)]
+pub struct RandIntRule {
+    min: i64,
+    max: i64,
+    distribution: Uniform<i64>,
+}
+
+impl ConstructRule for RandIntRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 2 {
+            return Err(FakerError::ArgumentCount {
+                rule: "randint".to_string(),
+                expected: "2".to_string(),
+                found: args.len(),
+            });
+        }
+
+        let min: i64 = args[0].trim().parse::<i64>()
+            .map_err(|e| FakerError::invalid_argument("randint", &args[0], e))?;
+        let max: i64 = args[1].trim().parse::<i64>()
+            .map_err(|e| FakerError::invalid_argument("randint", &args[1], e))?;
+        if min > max {
+            return Err(FakerError::invalid_argument("randint", &args[1], "max should be >= min"));
+        }
+
+        Ok(Box::new(RandIntRule {
+            min,
+            max,
+            distribution: Uniform::new_inclusive(min, max),
         }))
     }
 }
 
-impl GenerateRule for EnumRule {
-    fn generate_into(&self, into_type: VariableType) -> Result<VariableTypeValue, ()> {
+impl GenerateRule for RandIntRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let v = ctx.rng.sample(self.distribution);
         match into_type {
-            VariableType::Integer => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return match en.parse::<i64>() {
-                    Ok(e) => {
-                        Ok(VariableTypeValue::Integer(e))
-                    }
-                    Err(_) => {
-                        Err(())
-                    }
-                };
+            VariableType::Integer => Ok(VariableTypeValue::Integer(v)),
+            VariableType::Float => Ok(VariableTypeValue::Float(v as f64)),
+            VariableType::String => Ok(VariableTypeValue::String(v.to_string())),
+            VariableType::Decimal { precision, scale } => {
+                let value = parse_decimal(&v.to_string(), precision, scale)
+                    .map_err(|e| FakerError::invalid_value("randint", &v.to_string(), into_type.clone(), e))?;
+                Ok(VariableTypeValue::Decimal { value, scale })
             }
-            VariableType::Float => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return match en.parse::<f64>() {
-                    Ok(e) => {
-                        Ok(VariableTypeValue::Float(e))
-                    }
-                    Err(_) => {
-                        Err(())
-                    }
-                };
+            _ => Err(FakerError::unsupported_type("randint", into_type)),
+        }
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::Integer | VariableType::Float | VariableType::String => Ok(()),
+            VariableType::Decimal { precision, scale } => {
+                // 两端都能放进 decimal 中，中间的值自然也可以
+                for v in &[self.min, self.max] {
+                    parse_decimal(&v.to_string(), *precision, *scale)
+                        .map_err(|e| FakerError::invalid_value("randint", &v.to_string(), into_type.clone(), e))?;
+                }
+                Ok(())
             }
-            VariableType::String => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return Ok(VariableTypeValue::String(en.clone()));
+            _ => Err(FakerError::unsupported_type("randint", into_type.clone())),
+        }
+    }
+}
+
+// ------------ end of the RandIntRule ---------
+
+/// randfloat(min,max)
+/// randfloat(min,max,precision)
+/// randfloat(FLOAT,FLOAT,INTEGER)
+/// 在 [min, max) 中均匀随机取一个浮点数，不包含 max
+/// precision 为保留的小数位数，不指定时不做舍入
+#[derive(Debug, Clone)]
+pub struct RandFloatRule {
+    min: f64,
+    max: f64,
+    precision: Option<usize>,
+    distribution: Uniform<f64>,
+}
+
+/// randfloat 最多保留的小数位数，再多 f64 也表示不了
+const MAX_FLOAT_PRECISION: usize = 15;
+
+impl ConstructRule for RandFloatRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 2 && args.len() != 3 {
+            return Err(FakerError::ArgumentCount {
+                rule: "randfloat".to_string(),
+                expected: "2 or 3".to_string(),
+                found: args.len(),
+            });
+        }
+
+        let min: f64 = args[0].trim().parse::<f64>()
+            .map_err(|e| FakerError::invalid_argument("randfloat", &args[0], e))?;
+        let max: f64 = args[1].trim().parse::<f64>()
+            .map_err(|e| FakerError::invalid_argument("randfloat", &args[1], e))?;
+        if !min.is_finite() || !max.is_finite() || min >= max {
+            return Err(FakerError::invalid_argument("randfloat", &args[1], "bounds should be finite and max > min"));
+        }
+
+        let precision = match args.get(2) {
+            None => None,
+            Some(p) => {
+                let p = p.trim().parse::<usize>()
+                    .map_err(|e| FakerError::invalid_argument("randfloat", p, e))?;
+                if p > MAX_FLOAT_PRECISION {
+                    return Err(FakerError::invalid_argument("randfloat", &p.to_string(), "precision should be <= 15"));
+                }
+                let scale = 10f64.powi(p as i32);
+                if (min * scale).ceil() / scale >= max {
+                    return Err(FakerError::invalid_argument("randfloat", &p.to_string(),
+                                                            "no value with this precision lies in [min, max)"));
+                }
+                Some(p)
             }
-            VariableType::Date => {
-                Err(())
+        };
+
+        Ok(Box::new(RandFloatRule {
+            min,
+            max,
+            precision,
+            distribution: Uniform::new(min, max),
+        }))
+    }
+}
+
+impl GenerateRule for RandFloatRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let mut v = ctx.rng.sample(self.distribution);
+        if let Some(p) = self.precision {
+            // 舍入后仍要落在 [min, max) 中
+            let scale = 10f64.powi(p as i32);
+            v = (v * scale).round() / scale;
+            if v >= self.max {
+                v = (self.max * scale).ceil() / scale - 1.0 / scale;
+            }
+            if v < self.min {
+                v = (self.min * scale).ceil() / scale;
             }
         }
+        match into_type {
+            VariableType::Float => Ok(VariableTypeValue::Float(v)),
+            VariableType::String => Ok(VariableTypeValue::String(match self.precision {
+                Some(p) => format!("{:.*}", p, v),
+                None => v.to_string(),
+            })),
+            VariableType::Decimal { .. } => VariableTypeValue::from_f64(v, &into_type)
+                .ok_or_else(|| FakerError::invalid_value("randfloat", &v.to_string(), into_type, "out of range")),
+            _ => Err(FakerError::unsupported_type("randfloat", into_type)),
+        }
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::Float | VariableType::String => Ok(()),
+            VariableType::Decimal { .. } => {
+                for v in &[self.min, self.max] {
+                    VariableTypeValue::from_f64(*v, into_type)
+                        .ok_or_else(|| FakerError::invalid_value("randfloat", &v.to_string(), into_type.clone(), "out of range"))?;
+                }
+                Ok(())
+            }
+            _ => Err(FakerError::unsupported_type("randfloat", into_type.clone())),
+        }
     }
 }
 
-// ------------ end of the EnumRule ---------
+// ------------ end of the RandFloatRule ---------
+
+/// 解析规则参数中的时间，按 UTC 处理
+/// 支持 2020-01-01、2020-01-01 12:00:00、2020-01-01T12:00:00 和 RFC 3339（2020-01-01T12:00:00+08:00）
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(d) = DateTime::parse_from_rfc3339(s) {
+        return Some(d.with_timezone(&Utc));
+    }
+    for fmt in &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(d) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(DateTime::from_naive_utc_and_offset(d, Utc));
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|d| DateTime::from_naive_utc_and_offset(d, Utc))
+}
 
+/// 解析 1d、-2h、30m 这样的时间长度，单位为 ms、s、m、h、d、w，超出 Duration 范围时返回 None
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let idx = s.find(|c: char| c.is_ascii_alphabetic())?;
+    let n = s[..idx].parse::<i64>().ok()?;
+    match &s[idx..] {
+        "ms" => Duration::try_milliseconds(n),
+        "s" => Duration::try_seconds(n),
+        "m" => Duration::try_minutes(n),
+        "h" => Duration::try_hours(n),
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => None,
+    }
+}
+
+/// 把时间规则生成的时间转换成列的类型
+fn date_into(rule: &str, d: DateTime<Utc>, into_type: VariableType) -> Result<VariableTypeValue, FakerError> {
+    VariableTypeValue::from_datetime(d, &into_type)
+        .ok_or_else(|| FakerError::unsupported_type(rule, into_type))
+}
+
+fn check_date_type(rule: &str, into_type: &VariableType) -> Result<(), FakerError> {
+    match into_type {
+        VariableType::Date | VariableType::Timestamp | VariableType::Time | VariableType::String => Ok(()),
+        _ => Err(FakerError::unsupported_type(rule, into_type.clone())),
+    }
+}
+
+/// date(start,end)
+/// date(DATE,DATE)
+/// 在 [start, end] 中均匀随机取一个时间，精确到秒，包含两端
 #[derive(Debug, Clone)]
-pub struct EnumFileRule {
-    enumeration: Vec<String>,
+pub struct DateRule {
+    distribution: Uniform<i64>,
 }
 
-impl GenerateRule for EnumFileRule {
-    fn generate_into(&self, into_type: VariableType) -> Result<VariableTypeValue, ()> {
+impl ConstructRule for DateRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 2 {
+            return Err(FakerError::ArgumentCount {
+                rule: "date".to_string(),
+                expected: "2".to_string(),
+                found: args.len(),
+            });
+        }
+
+        let start = parse_datetime(&args[0])
+            .ok_or_else(|| FakerError::invalid_argument("date", &args[0], "expected a date like 2020-01-01 00:00:00"))?;
+        let end = parse_datetime(&args[1])
+            .ok_or_else(|| FakerError::invalid_argument("date", &args[1], "expected a date like 2020-01-01 00:00:00"))?;
+        if start > end {
+            return Err(FakerError::invalid_argument("date", &args[1], "end should not be before start"));
+        }
+
+        Ok(Box::new(DateRule {
+            distribution: Uniform::new_inclusive(start.timestamp(), end.timestamp()),
+        }))
+    }
+}
+
+impl GenerateRule for DateRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let secs = ctx.rng.sample(self.distribution);
+        let d = Utc.timestamp_opt(secs, 0).single().ok_or_else(|| FakerError::InvalidValue {
+            rule: "date".to_string(),
+            value: secs.to_string(),
+            var_type: into_type.clone(),
+            cause: None,
+        })?;
+        date_into("date", d, into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_date_type("date", into_type)
+    }
+}
+
+// ------------ end of the DateRule ---------
+
+/// date_increase(start,step)
+/// date_increase(DATE,DURATION)
+/// 第 n 行（从 0 开始）的值为 start + n * step，step 例如 1h、1d、-30m
+#[derive(Debug, Clone)]
+pub struct DateIncreaseRule {
+    start: DateTime<Utc>,
+    step: Duration,
+}
+
+impl ConstructRule for DateIncreaseRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 2 {
+            return Err(FakerError::ArgumentCount {
+                rule: "date_increase".to_string(),
+                expected: "2".to_string(),
+                found: args.len(),
+            });
+        }
+
+        let start = parse_datetime(&args[0])
+            .ok_or_else(|| FakerError::invalid_argument("date_increase", &args[0], "expected a date like 2020-01-01 00:00:00"))?;
+        let step = parse_duration(&args[1])
+            .ok_or_else(|| FakerError::invalid_argument("date_increase", &args[1], "expected a duration like 1d, 2h or 30m"))?;
+
+        Ok(Box::new(DateIncreaseRule {
+            start,
+            step,
+        }))
+    }
+}
+
+impl GenerateRule for DateIncreaseRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let overflow = || FakerError::Overflow {
+            rule: "date_increase".to_string(),
+            row: ctx.row,
+        };
+        let millis = i64::try_from(ctx.row as i128 * self.step.num_milliseconds() as i128).map_err(|_| overflow())?;
+        let d = Duration::try_milliseconds(millis).and_then(|m| self.start.checked_add_signed(m)).ok_or_else(overflow)?;
+        date_into("date_increase", d, into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_date_type("date_increase", into_type)
+    }
+}
+
+// ------------ end of the DateIncreaseRule ---------
+
+/// now()
+/// 读取配置时的时间，同一次生成中每一行都相同
+#[derive(Debug, Clone)]
+pub struct NowRule {
+    now: DateTime<Utc>,
+}
+
+impl ConstructRule for NowRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if !args.is_empty() {
+            return Err(FakerError::ArgumentCount {
+                rule: "now".to_string(),
+                expected: "0".to_string(),
+                found: args.len(),
+            });
+        }
+
+        Ok(Box::new(NowRule {
+            now: Utc::now(),
+        }))
+    }
+}
+
+impl GenerateRule for NowRule {
+    fn generate_into(&self, into_type: VariableType, _ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        date_into("now", self.now, into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_date_type("now", into_type)
+    }
+}
+
+// ------------ end of the NowRule ---------
+
+/// uuid()
+/// 随机的第 4 版 UUID，可以输出为 Uuid 或 String
+#[derive(Debug, Clone)]
+pub struct UuidRule;
+
+impl ConstructRule for UuidRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if !args.is_empty() {
+            return Err(FakerError::ArgumentCount {
+                rule: "uuid".to_string(),
+                expected: "0".to_string(),
+                found: args.len(),
+            });
+        }
+        Ok(Box::new(UuidRule))
+    }
+}
+
+impl GenerateRule for UuidRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let u = uuid_v4(ctx.rng.gen());
         match into_type {
-            VariableType::Integer => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return match en.parse::<i64>() {
-                    Ok(e) => {
-                        Ok(VariableTypeValue::Integer(e))
-                    }
-                    Err(_) => {
-                        Err(())
-                    }
-                };
+            VariableType::Uuid => Ok(VariableTypeValue::Uuid(u)),
+            VariableType::String => Ok(VariableTypeValue::String(uuid_to_string(u))),
+            t => Err(FakerError::unsupported_type("uuid", t)),
+        }
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::Uuid | VariableType::String => Ok(()),
+            t => Err(FakerError::unsupported_type("uuid", t.clone())),
+        }
+    }
+}
+
+// ------------ end of the UuidRule ---------
+
+/// format(template,arg1,arg2,...)
+/// 用参数依次替换模板中的 {}，{0} {1} 按位置替换，{{ 和 }} 输出 { 和 }
+/// 参数可以是嵌套的规则、同一行中其他列的列名或引号中的字符串，例如
+/// email||string||format("{}.{}@example.com",first_name,randint(1,99))
+/// 被引用的列会先生成，null 替换为空字符串
+#[derive(Debug, Clone)]
+pub struct FormatRule {
+    template: Vec<Piece>,
+    args: Vec<FormatArg>,
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Text(String),
+    Arg(usize),
+}
+
+#[derive(Debug, Clone)]
+enum FormatArg {
+    Rule(Box<dyn GenerateRule>),
+    Column(String),
+    Text(String),
+}
+
+impl ConstructRule for FormatRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let call = parse_rule(&s)?;
+        let template = match call.args.first() {
+            Some(Argument::Value { text, .. }) => text,
+            Some(Argument::Call(c)) => {
+                return Err(FakerError::invalid_argument("format", &c.source, "the template should be a string"));
             }
-            VariableType::Float => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return match en.parse::<f64>() {
-                    Ok(e) => {
-                        Ok(VariableTypeValue::Float(e))
-                    }
-                    Err(_) => {
-                        Err(())
+            None => return Err(FakerError::ArgumentCount {
+                rule: "format".to_string(),
+                expected: "at least 1".to_string(),
+                found: 0,
+            }),
+        };
+
+        let args = format_args(&call.args[1..])?;
+        let template = parse_template(template, args.len())
+            .map_err(|e| FakerError::invalid_argument("format", template, e))?;
+        Ok(Box::new(FormatRule { template, args }))
+    }
+}
+
+/// 把模板拆成文本和参数的序号
+fn parse_template(template: &str, arg_count: usize) -> Result<Vec<Piece>, String> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut idx = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(d) if d.is_ascii_digit() => idx.push(d),
+                        _ => return Err("expected `{}` or `{N}`".to_string()),
                     }
+                }
+                let idx = if idx.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    idx.parse::<usize>().map_err(|e| e.to_string())?
                 };
+                if idx >= arg_count {
+                    return Err(format!("placeholder {} has no argument", idx));
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Arg(idx));
             }
-            VariableType::String => {
-                let mut rng = rand::thread_rng();
-                let len = self.enumeration.len();
-                let ran = rng.gen_range(0, len);
-                let en = self.enumeration.get(ran).unwrap();
-                return Ok(VariableTypeValue::String(en.clone()));
+            '}' => return Err("unmatched `}`, use `}}`".to_string()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+impl GenerateRule for FormatRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        if into_type != VariableType::String {
+            return Err(FakerError::unsupported_type("format", into_type));
+        }
+        // 每个参数只生成一次，{0}{0} 得到两个相同的值
+        let values = format_values(&self.args, ctx)?;
+        let mut s = String::new();
+        for piece in &self.template {
+            match piece {
+                Piece::Text(text) => s.push_str(text),
+                Piece::Arg(idx) => s.push_str(&values[*idx]),
+            }
+        }
+        Ok(VariableTypeValue::String(s))
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::String => Ok(()),
+            t => Err(FakerError::unsupported_type("format", t.clone())),
+        }
+    }
+
+    fn references(&self) -> Vec<String> {
+        format_references(&self.args)
+    }
+
+    fn check_references(&self, types: &[VariableType], _into_type: &VariableType) -> Result<(), FakerError> {
+        format_check_references(&self.args, types)
+    }
+
+    fn foreign_keys(&self) -> Vec<(String, String)> {
+        format_foreign_keys(&self.args)
+    }
+}
+
+/// format 和 concat 的参数：嵌套的规则、列名或引号中的字符串
+fn format_args(args: &[Argument]) -> Result<Vec<FormatArg>, FakerError> {
+    let mut out = vec![];
+    for arg in args {
+        out.push(match arg {
+            Argument::Call(c) => {
+                let rule = construct_from_str(&c.source)?;
+                rule.check_type(&VariableType::String)?;
+                FormatArg::Rule(rule)
             }
-            VariableType::Date => {
-                Err(())
+            Argument::Value { text, quoted: true } => FormatArg::Text(text.clone()),
+            Argument::Value { text, .. } => FormatArg::Column(text.clone()),
+        });
+    }
+    Ok(out)
+}
+
+/// 依次生成每个参数的文本
+fn format_values(args: &[FormatArg], ctx: &mut GenerateContext) -> Result<Vec<String>, FakerError> {
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        values.push(match arg {
+            FormatArg::Rule(rule) => match rule.generate_into(VariableType::String, ctx)? {
+                VariableTypeValue::String(s) => s,
+                _ => String::new(),
+            },
+            FormatArg::Column(name) => column(ctx, name)?.text().unwrap_or_default(),
+            FormatArg::Text(text) => text.clone(),
+        });
+    }
+    Ok(values)
+}
+
+fn format_references(args: &[FormatArg]) -> Vec<String> {
+    args.iter().flat_map(|arg| match arg {
+        FormatArg::Rule(rule) => rule.references(),
+        FormatArg::Column(name) => vec![name.clone()],
+        FormatArg::Text(_) => vec![],
+    }).collect()
+}
+
+/// 嵌套的规则生成的是文本，按 format_references 的顺序把引用的列的类型交给各自的规则检查
+fn format_check_references(args: &[FormatArg], mut types: &[VariableType]) -> Result<(), FakerError> {
+    for arg in args {
+        match arg {
+            FormatArg::Rule(rule) => {
+                let (own, rest) = types.split_at(rule.references().len());
+                rule.check_references(own, &VariableType::String)?;
+                types = rest;
             }
+            FormatArg::Column(_) => types = &types[1..],
+            FormatArg::Text(_) => {}
         }
     }
+    Ok(())
 }
 
-impl ConstructRule for EnumFileRule {
-    fn construct(s: String) -> Result<Box<Self>, ()> {
-        let args = remove_parentheses(s.borrow())?;
+fn format_foreign_keys(args: &[FormatArg]) -> Vec<(String, String)> {
+    args.iter().flat_map(|arg| match arg {
+        FormatArg::Rule(rule) => rule.foreign_keys(),
+        _ => vec![],
+    }).collect()
+}
+
+/// 同一行中已经生成的列，生成顺序保证被引用的列已经生成
+fn column<'a>(ctx: &GenerateContext<'a>, name: &str) -> Result<&'a ColumnValue, FakerError> {
+    ctx.columns.get(name).ok_or_else(|| FakerError::UnknownColumn(name.to_string()))
+}
+
+// ------------ end of the FormatRule ---------
+
+/// concat(arg1,arg2,...)
+/// 把参数的文本依次拼接起来，参数与 format 相同，例如
+/// full_name||string||concat(first_name," ",last_name)
+#[derive(Debug, Clone)]
+pub struct ConcatRule {
+    args: Vec<FormatArg>,
+}
+
+impl ConstructRule for ConcatRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let call = parse_rule(&s)?;
+        if call.args.is_empty() {
+            return Err(FakerError::ArgumentCount {
+                rule: "concat".to_string(),
+                expected: "at least 1".to_string(),
+                found: 0,
+            });
+        }
+        Ok(Box::new(ConcatRule {
+            args: format_args(&call.args)?,
+        }))
+    }
+}
+
+impl GenerateRule for ConcatRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        if into_type != VariableType::String {
+            return Err(FakerError::unsupported_type("concat", into_type));
+        }
+        Ok(VariableTypeValue::String(format_values(&self.args, ctx)?.concat()))
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::String => Ok(()),
+            t => Err(FakerError::unsupported_type("concat", t.clone())),
+        }
+    }
+
+    fn references(&self) -> Vec<String> {
+        format_references(&self.args)
+    }
+
+    fn check_references(&self, types: &[VariableType], _into_type: &VariableType) -> Result<(), FakerError> {
+        format_check_references(&self.args, types)
+    }
+
+    fn foreign_keys(&self) -> Vec<(String, String)> {
+        format_foreign_keys(&self.args)
+    }
+}
+
+// ------------ end of the ConcatRule ---------
+
+/// ref(column)
+/// 与同一行中另一列的值相同，类型不同时按文本转换，例如 int 列转换成 float 或 string
+#[derive(Debug, Clone)]
+pub struct RefRule {
+    column: String,
+}
+
+impl ConstructRule for RefRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
         if args.len() != 1 {
-            return Err(());
+            return Err(FakerError::ArgumentCount {
+                rule: "ref".to_string(),
+                expected: "1".to_string(),
+                found: args.len(),
+            });
         }
+        Ok(Box::new(RefRule {
+            column: args[0].clone(),
+        }))
+    }
+}
 
-        let path = args[0];
-        let file = match read_to_string(path) {
-            Ok(e) => { e }
-            Err(_) => { return Err(()); }
+impl GenerateRule for RefRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        column_into("ref", column(ctx, &self.column)?, into_type)
+    }
+
+    fn check_type(&self, _into_type: &VariableType) -> Result<(), FakerError> {
+        Ok(())
+    }
+
+    fn check_references(&self, types: &[VariableType], into_type: &VariableType) -> Result<(), FakerError> {
+        if convertible(&types[0], into_type) {
+            return Ok(());
+        }
+        Err(FakerError::invalid_argument("ref", &self.column,
+                                         format!("cannot convert a {:?} column to {:?}", types[0], into_type)))
+    }
+
+    fn references(&self) -> Vec<String> {
+        vec![self.column.clone()]
+    }
+}
+
+/// 类型为 from 的列的值能否转换成 into 类型。string 列的值要到生成时才知道，总是认为可以
+fn convertible(from: &VariableType, into: &VariableType) -> bool {
+    match (from, into) {
+        (VariableType::Null, _) | (VariableType::String, _) | (_, VariableType::String) => true,
+        (VariableType::Integer, VariableType::Integer | VariableType::Float | VariableType::Decimal { .. }) => true,
+        (VariableType::Float | VariableType::Decimal { .. }, VariableType::Float | VariableType::Decimal { .. }) => true,
+        (VariableType::Date | VariableType::Timestamp, VariableType::Date | VariableType::Timestamp | VariableType::Time) => true,
+        (from, into) => from == into,
+    }
+}
+
+/// 把另一列的值转换成 into_type，类型相同时直接复制，否则按文本转换
+fn column_into(rule: &str, c: &ColumnValue, into_type: VariableType) -> Result<VariableTypeValue, FakerError> {
+    if c.value.matches(&into_type) {
+        return Ok(c.value.clone());
+    }
+    match c.text() {
+        Some(text) => parse_enumeration(rule, &text, into_type),
+        None => Ok(VariableTypeValue::Null),
+    }
+}
+
+// ------------ end of the RefRule ---------
+
+/// date_after(column,min,max)
+/// date_after(COLUMN,DURATION,DURATION)
+/// 在同一行中另一个时间列之后 [min, max] 的范围内均匀随机取一个时间，精确到毫秒，
+/// 例如 end_date||date||date_after(start_date,1d,30d)。被引用的列为 null 时也输出 null
+#[derive(Debug, Clone)]
+pub struct DateAfterRule {
+    column: String,
+    distribution: Uniform<i64>,
+}
+
+impl ConstructRule for DateAfterRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 3 {
+            return Err(FakerError::ArgumentCount {
+                rule: "date_after".to_string(),
+                expected: "3".to_string(),
+                found: args.len(),
+            });
+        }
+        let min = parse_duration(&args[1])
+            .ok_or_else(|| FakerError::invalid_argument("date_after", &args[1], "expected a duration like 1d, 2h or 30m"))?;
+        let max = parse_duration(&args[2])
+            .ok_or_else(|| FakerError::invalid_argument("date_after", &args[2], "expected a duration like 1d, 2h or 30m"))?;
+        if max < min {
+            return Err(FakerError::invalid_argument("date_after", &args[2], "max should be >= min"));
+        }
+        Ok(Box::new(DateAfterRule {
+            column: args[0].clone(),
+            distribution: Uniform::new_inclusive(min.num_milliseconds(), max.num_milliseconds()),
+        }))
+    }
+}
+
+impl GenerateRule for DateAfterRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let start = match &column(ctx, &self.column)?.value {
+            VariableTypeValue::Date(d) | VariableTypeValue::Timestamp(d) => *d,
+            VariableTypeValue::Null => return Ok(VariableTypeValue::Null),
+            v => {
+                let text = format!("{:?}", v);
+                return Err(FakerError::invalid_value("date_after", &text, VariableType::Date, "expected a date column"));
+            }
         };
-        let v: Vec<_> = file.lines().filter(|ss| {
-            ss.ne(&"")
-        }).map(|ss| { String::from(ss) }).collect();
+        let millis = ctx.rng.sample(self.distribution);
+        let d = Duration::try_milliseconds(millis).and_then(|m| start.checked_add_signed(m))
+            .ok_or_else(|| FakerError::Overflow { rule: "date_after".to_string(), row: ctx.row })?;
+        date_into("date_after", d, into_type)
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        check_date_type("date_after", into_type)
+    }
 
-        Ok(Box::new(EnumFileRule {
-            enumeration: v
+    fn check_references(&self, types: &[VariableType], _into_type: &VariableType) -> Result<(), FakerError> {
+        match types[0] {
+            VariableType::Date | VariableType::Timestamp | VariableType::Null => Ok(()),
+            ref t => Err(FakerError::invalid_argument("date_after", &self.column,
+                                                      format!("expected a date column, found {:?}", t))),
+        }
+    }
+
+    fn references(&self) -> Vec<String> {
+        vec![self.column.clone()]
+    }
+}
+
+// ------------ end of the DateAfterRule ---------
+
+/// fk(table.column)
+/// 外键，从父表已经生成的行中均匀随机地取一行，输出该行 column 列的值，
+/// 例如 user_id||int||fk(users.id)。只能在 schema 文件中使用，父表会先生成
+#[derive(Debug, Clone)]
+pub struct FkRule {
+    table: String,
+    column: String,
+}
+
+impl ConstructRule for FkRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 1 {
+            return Err(FakerError::ArgumentCount {
+                rule: "fk".to_string(),
+                expected: "1".to_string(),
+                found: args.len(),
+            });
+        }
+        let (table, column) = match args[0].find('.') {
+            Some(idx) if idx > 0 && idx + 1 < args[0].len() => (&args[0][..idx], &args[0][idx + 1..]),
+            _ => return Err(FakerError::invalid_argument("fk", &args[0], "expected `table.column`")),
+        };
+        Ok(Box::new(FkRule {
+            table: table.to_string(),
+            column: column.to_string(),
         }))
     }
 }
+
+impl GenerateRule for FkRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        let tables = ctx.tables
+            .ok_or_else(|| FakerError::UnknownTable(self.table.clone()))?;
+        let count = tables.row_count(&self.table)
+            .ok_or_else(|| FakerError::UnknownTable(self.table.clone()))?;
+        if count == 0 {
+            return Err(FakerError::invalid_argument("fk", &self.table, "the parent table has no rows"));
+        }
+        let row = ctx.rng.gen_range(0, count);
+        column_into("fk", &tables.value(&self.table, &self.column, row)?, into_type)
+    }
+
+    fn check_type(&self, _into_type: &VariableType) -> Result<(), FakerError> {
+        Ok(())
+    }
+
+    fn foreign_keys(&self) -> Vec<(String, String)> {
+        vec![(self.table.clone(), self.column.clone())]
+    }
+}
@@ -0,0 +1,372 @@
+use crate::configuration::definitions::{GenerateRule, VariableType, VariableTypeValue, ConstructRule, remove_parentheses, GenerateContext};
+use crate::error::FakerError;
+use rand::Rng;
+use rand::distributions::Uniform;
+
+/// 没有上限的重复（* + 和 {n,}）默认最多比下限多重复的次数
+pub const DEFAULT_MAX_REPEAT: u32 = 8;
+
+/// {n,m} 中的次数和 max_repeat 的上限，避免一个值生成过长的字符串
+pub const MAX_REPEAT_BOUND: u32 = 1000;
+
+/// 可打印的 ASCII 字符，. 和 [^...] 从中选取
+const PRINTABLE: (char, char) = (' ', '~');
+
+/// regex(pattern)
+/// regex(pattern,max_repeat)
+/// 生成匹配正则表达式的随机字符串，例如 regex("ORD-[A-Z]{3}-\d{6}")
+/// 支持字符、转义、. 、[a-z] 和 [^...] 字符类、\d \w \s 及其大写形式、
+/// 重复 * + ? {n} {n,} {n,m}、| 和 (...) (?:...) 分组，开头的 ^ 和结尾的 $ 会被忽略
+/// 没有上限的重复最多比下限多重复 max_repeat 次，默认 8
+#[derive(Debug, Clone)]
+pub struct RegexRule {
+    node: Node,
+}
+
+/// 正则表达式的语法树
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(char),
+    /// 字符类，闭区间的列表，不为空
+    Class(Vec<(char, char)>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: u32,
+    },
+}
+
+impl ConstructRule for RegexRule {
+    fn construct(s: String) -> Result<Box<Self>, FakerError> {
+        let args = remove_parentheses(&s)?;
+        if args.len() != 1 && args.len() != 2 {
+            return Err(FakerError::ArgumentCount {
+                rule: "regex".to_string(),
+                expected: "1 or 2".to_string(),
+                found: args.len(),
+            });
+        }
+        let max_repeat = match args.get(1) {
+            None => DEFAULT_MAX_REPEAT,
+            Some(m) => m.trim().parse::<u32>()
+                .map_err(|e| FakerError::invalid_argument("regex", m, e))?,
+        };
+        if max_repeat > MAX_REPEAT_BOUND {
+            return Err(FakerError::invalid_argument("regex", &args[1],
+                                                    format!("max_repeat should not exceed {}", MAX_REPEAT_BOUND)));
+        }
+        let mut parser = Parser {
+            chars: args[0].chars().collect(),
+            pos: 0,
+            max_repeat,
+        };
+        let node = parser.parse()
+            .map_err(|e| FakerError::invalid_argument("regex", &args[0], e))?;
+        Ok(Box::new(RegexRule { node }))
+    }
+}
+
+impl GenerateRule for RegexRule {
+    fn generate_into(&self, into_type: VariableType, ctx: &mut GenerateContext) -> Result<VariableTypeValue, FakerError> {
+        match into_type {
+            VariableType::String => {
+                let mut s = String::new();
+                self.node.generate(&mut s, ctx);
+                Ok(VariableTypeValue::String(s))
+            }
+            t => Err(FakerError::unsupported_type("regex", t)),
+        }
+    }
+
+    fn check_type(&self, into_type: &VariableType) -> Result<(), FakerError> {
+        match into_type {
+            VariableType::String => Ok(()),
+            t => Err(FakerError::unsupported_type("regex", t.clone())),
+        }
+    }
+}
+
+impl Node {
+    fn generate(&self, out: &mut String, ctx: &mut GenerateContext) {
+        match self {
+            Node::Literal(c) => out.push(*c),
+            Node::Class(ranges) => {
+                let total: u32 = ranges.iter().map(|(lo, hi)| *hi as u32 - *lo as u32 + 1).sum();
+                let mut n = ctx.rng.gen_range(0, total);
+                for (lo, hi) in ranges {
+                    let size = *hi as u32 - *lo as u32 + 1;
+                    if n < size {
+                        // 区间在解析时已经排除了代理项，这里总是合法的字符
+                        out.push(std::char::from_u32(*lo as u32 + n).unwrap());
+                        return;
+                    }
+                    n -= size;
+                }
+            }
+            Node::Concat(nodes) => {
+                for n in nodes {
+                    n.generate(out, ctx);
+                }
+            }
+            Node::Alternation(nodes) => {
+                let idx = ctx.rng.gen_range(0, nodes.len());
+                nodes[idx].generate(out, ctx);
+            }
+            Node::Repeat { node, min, max } => {
+                for _ in 0..ctx.rng.sample(Uniform::new_inclusive(*min, *max)) {
+                    node.generate(out, ctx);
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    max_repeat: u32,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse(&mut self) -> Result<Node, String> {
+        self.eat('^');
+        let node = self.alternation()?;
+        match self.peek() {
+            None => Ok(node),
+            Some(c) => Err(format!("unexpected `{}` at {}", c, self.pos + 1)),
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alternation(branches) })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![];
+        loop {
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                // 结尾的 $ 会被忽略
+                Some('$') if self.pos + 1 == self.chars.len() => {
+                    self.pos += 1;
+                }
+                _ => {
+                    let atom = self.atom()?;
+                    nodes.push(self.quantifiers(atom)?);
+                }
+            }
+        }
+        Ok(if nodes.len() == 1 { nodes.pop().unwrap() } else { Node::Concat(nodes) })
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.bump().unwrap() {
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err("only (?:...) groups are supported".to_string());
+                }
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return Err("missing `)`".to_string());
+                }
+                Ok(node)
+            }
+            '[' => self.class(),
+            '.' => Ok(Node::Class(vec![PRINTABLE])),
+            '\\' => self.escape(),
+            c @ ('*' | '+' | '?') => Err(format!("nothing to repeat before `{}`", c)),
+            c => Ok(Node::Literal(c)),
+        }
+    }
+
+    /// 紧跟在 atom 后面的 * + ? {n,m}，懒惰匹配的 ? 会被忽略
+    fn quantifiers(&mut self, mut node: Node) -> Result<Node, String> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => {
+                    let start = self.pos;
+                    match self.counted()? {
+                        Some(r) => {
+                            node = self.repeat(node, r.0, r.1);
+                            self.eat('?');
+                            continue;
+                        }
+                        // 不是 {n,m} 的 { 是普通字符
+                        None => {
+                            self.pos = start;
+                            return Ok(node);
+                        }
+                    }
+                }
+                _ => return Ok(node),
+            };
+            self.pos += 1;
+            node = self.repeat(node, min, max);
+            self.eat('?');
+        }
+    }
+
+    fn repeat(&self, node: Node, min: u32, max: Option<u32>) -> Node {
+        Node::Repeat {
+            node: Box::new(node),
+            min,
+            max: max.unwrap_or_else(|| min.saturating_add(self.max_repeat)),
+        }
+    }
+
+    /// 解析 {n}、{n,}、{n,m}，格式不对时返回 None
+    fn counted(&mut self) -> Result<Option<(u32, Option<u32>)>, String> {
+        self.pos += 1;
+        let min = match self.number() {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+        let max = if self.eat(',') {
+            self.number()
+        } else {
+            Some(min)
+        };
+        if !self.eat('}') {
+            return Ok(None);
+        }
+        if max.is_some_and(|m| m < min) {
+            return Err(format!("invalid repetition {{{},{}}}", min, max.unwrap()));
+        }
+        if max.unwrap_or(min) > MAX_REPEAT_BOUND {
+            return Err(format!("repetition count should not exceed {}", MAX_REPEAT_BOUND));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        // 超出 u32 的次数按 u32::MAX 处理，由 counted 报错
+        Some(self.chars[start..self.pos].iter().collect::<String>().parse().unwrap_or(u32::MAX))
+    }
+
+    /// [a-z0-9_] 或 [^...]
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut first = true;
+        loop {
+            let c = self.bump().ok_or_else(|| "missing `]`".to_string())?;
+            // 开头的 ] 是普通字符
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let lo = match c {
+                '\\' => match self.escape()? {
+                    Node::Literal(l) => l,
+                    Node::Class(r) => {
+                        ranges.extend(r);
+                        continue;
+                    }
+                    _ => unreachable!(),
+                },
+                c => c,
+            };
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|c| *c != ']') {
+                self.pos += 1;
+                let hi = match self.bump().unwrap() {
+                    '\\' => match self.escape()? {
+                        Node::Literal(h) => h,
+                        _ => return Err("invalid range in character class".to_string()),
+                    },
+                    h => h,
+                };
+                if hi < lo {
+                    return Err(format!("invalid range {}-{}", lo, hi));
+                }
+                ranges.push((lo, hi));
+            } else {
+                ranges.push((lo, lo));
+            }
+        }
+        if ranges.iter().any(|(lo, hi)| *lo as u32 <= 0xDFFF && *hi as u32 >= 0xD800) {
+            return Err("character class contains surrogates".to_string());
+        }
+        if negated {
+            ranges = complement(&ranges);
+            if ranges.is_empty() {
+                return Err("negated character class matches nothing".to_string());
+            }
+        }
+        Ok(Node::Class(ranges))
+    }
+
+    /// \ 之后的部分，\d \w \s 返回字符类，其他返回字符
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = self.bump().ok_or_else(|| "unexpected end after `\\`".to_string())?;
+        let class = |ranges: &[(char, char)]| Node::Class(ranges.to_vec());
+        Ok(match c {
+            'd' => class(&[('0', '9')]),
+            'w' => class(&[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')]),
+            's' => class(&[(' ', ' ')]),
+            'D' => Node::Class(complement(&[('0', '9')])),
+            'W' => Node::Class(complement(&[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')])),
+            'S' => Node::Class(complement(&[(' ', ' ')])),
+            'n' => Node::Literal('\n'),
+            't' => Node::Literal('\t'),
+            'r' => Node::Literal('\r'),
+            c if c.is_ascii_alphanumeric() => return Err(format!("unsupported escape `\\{}`", c)),
+            c => Node::Literal(c),
+        })
+    }
+}
+
+/// 可打印的 ASCII 字符中不属于 ranges 的部分
+fn complement(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut out = vec![];
+    let mut next = PRINTABLE.0 as u32;
+    let mut sorted = ranges.to_vec();
+    sorted.sort();
+    for (lo, hi) in sorted {
+        let (lo, hi) = (lo as u32, hi as u32);
+        if lo > next && next <= PRINTABLE.1 as u32 {
+            out.push((next, (lo - 1).min(PRINTABLE.1 as u32)));
+        }
+        next = next.max(hi + 1);
+    }
+    if next <= PRINTABLE.1 as u32 {
+        out.push((next, PRINTABLE.1 as u32));
+    }
+    // 都在 ASCII 范围内，转换总是成功
+    out.into_iter()
+        .map(|(lo, hi)| (std::char::from_u32(lo).unwrap(), std::char::from_u32(hi).unwrap()))
+        .collect()
+}